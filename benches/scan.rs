@@ -0,0 +1,93 @@
+//! Times `Scanner` on a synthetic tree: one walker thread against one per CPU.
+//!
+//! `cargo bench --bench scan -- [--files N] [--runs N]`
+
+use std::{
+    env, fs,
+    path::Path,
+    process,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result, bail};
+use hyprconf::scan::Scanner;
+
+/// Write a config tree with `files` files spread over conf.d (nested), themes, plugins and scripts.
+fn generate(root: &Path, files: usize) -> Result<()> {
+    fs::write(root.join("hyprland.conf"), "# main config\nsource = conf.d/*.conf\n")?;
+    for i in 0..files {
+        let (dir, name, body) = match i % 10 {
+            0..=5 => (format!("conf.d/group{}/sub{}", i % 7, i % 3), format!("{i:05}-module.conf"), "general {\n    gaps_in = 5\n}\n"),
+            6 | 7 => ("themes".to_string(), format!("theme{i}.conf"), "$accent = rgba(89b4faee)\n"),
+            8 => ("plugins".to_string(), format!("plugin{i}.conf"), "plugin {\n}\n"),
+            _ => (format!("scripts/lib{}", i % 5), format!("script{i}.sh"), "#!/bin/sh\necho hi\n"),
+        };
+        let dir = root.join(dir);
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(name);
+        fs::write(&path, format!("# synthetic file {i} - generated for benchmarking\n{}", body.repeat(10)))?;
+        #[cfg(unix)]
+        if path.extension().is_some_and(|e| e == "sh") {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+        }
+    }
+    Ok(())
+}
+
+/// Average (time to first entry, total time) over `runs` scans.
+fn time_scan(scanner: &Scanner, runs: u32) -> Result<(Duration, Duration, usize)> {
+    let (mut first, mut total, mut count) = (Duration::ZERO, Duration::ZERO, 0);
+    for _ in 0..runs {
+        let start = Instant::now();
+        let first_at = OnceLock::new();
+        count = scanner.scan_with(|_| {
+            first_at.get_or_init(|| start.elapsed());
+        })?
+        .len();
+        total += start.elapsed();
+        first += first_at.get().copied().unwrap_or_default();
+    }
+    Ok((first / runs, total / runs, count))
+}
+
+/// `--files N` and `--runs N`; anything else cargo passes (e.g. `--bench`) is ignored.
+fn parse_args() -> Result<(usize, u32)> {
+    let (mut files, mut runs) = (5000, 5);
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg != "--files" && arg != "--runs" {
+            continue;
+        }
+        let Some(value) = args.next() else { bail!("{arg} needs a value") };
+        let parse_err = || format!("{arg}: not a number: {value}");
+        if arg == "--files" {
+            files = value.parse().with_context(parse_err)?;
+        } else {
+            runs = value.parse().with_context(parse_err)?;
+        }
+    }
+    Ok((files, runs.max(1)))
+}
+
+fn main() -> Result<()> {
+    let (files, runs) = parse_args()?;
+    let root = env::temp_dir().join(format!("hyprconf-bench-{}", process::id()));
+    fs::create_dir_all(&root).with_context(|| format!("creating {}", root.display()))?;
+    let result = (|| {
+        generate(&root, files)?;
+        // Measure the walk itself: no scan cache, and a warm page cache for both runs
+        let parallel = Scanner::new(&root).cache(false);
+        let single = parallel.clone().threads(1);
+        time_scan(&parallel, 1)?;
+        println!("{files} files, {runs} run(s) each; baseline is the same Scanner limited to one walker thread");
+        for (label, scanner) in [("1 thread", single), ("parallel", parallel)] {
+            let (first, total, count) = time_scan(&scanner, runs)?;
+            println!("{label:<10}  {count} entries  first entry {first:>10.2?}  complete {total:>10.2?}");
+        }
+        Ok(())
+    })();
+    fs::remove_dir_all(&root).with_context(|| format!("removing {}", root.display()))?;
+    result
+}
@@ -0,0 +1,121 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+
+use crate::{
+    git,
+    model::ConfigLine,
+    parse::{self, LineKind},
+};
+
+/// Commits scanned for the value history.
+const HISTORY_LIMIT: &str = "200";
+
+#[derive(Debug, Clone)]
+pub struct Commit {
+    pub id: String,
+    pub author: String,
+    pub date: String,
+    pub summary: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Blame {
+    pub path: PathBuf,
+    pub line: usize,
+    pub text: String,
+    pub option: Option<String>,
+    /// Commit that last touched the line; None while the line is uncommitted
+    pub last: Option<Commit>,
+    /// Each value the option took in this file, oldest first; the working tree comes last
+    /// (without a commit) when it differs from HEAD
+    pub history: Vec<(Option<Commit>, Option<String>)>,
+}
+
+const LOG_FORMAT: &str = "--format=%h%x1f%an%x1f%ad%x1f%s";
+
+fn parse_commits(raw: &str) -> Vec<Commit> {
+    raw.lines()
+        .filter_map(|l| {
+            let mut f = l.split('\x1f');
+            Some(Commit {
+                id: f.next()?.to_string(),
+                author: f.next()?.to_string(),
+                date: f.next()?.to_string(),
+                summary: f.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Last value `option` is set to in a file's contents.
+fn option_value(src: &str, option: &str) -> Option<String> {
+    parse::parse_str(src).into_iter().rev().find_map(|l| match &l.kind {
+        LineKind::Assign { value, .. } if l.option_path().as_deref() == Some(option) => Some(value.clone()),
+        _ => None,
+    })
+}
+
+pub fn blame(line: &ConfigLine) -> Result<Blame> {
+    let path = line.path.canonicalize().unwrap_or_else(|_| line.path.clone());
+    let dir = path.parent().context("file has no parent directory")?;
+    let name = path.file_name().context("not a file")?.to_string_lossy().into_owned();
+    if git::work_tree(&path).is_none() {
+        bail!("{} is not in a git repository", line.path.display());
+    }
+
+    let range = format!("{0},{0}", line.line);
+    let porcelain = git::git(dir, &["blame", "--porcelain", "-L", &range, "--", &name])?;
+    let id = porcelain.split_whitespace().next().unwrap_or_default();
+    let last = if id.is_empty() || id.chars().all(|c| c == '0') {
+        None
+    } else {
+        let raw = git::git(dir, &["log", "-1", LOG_FORMAT, "--date=format:%Y-%m-%d %H:%M", id])?;
+        parse_commits(&raw).pop()
+    };
+
+    let mut history: Vec<(Option<Commit>, Option<String>)> = Vec::new();
+    if let Some(option) = &line.option {
+        let raw = git::git(dir, &["log", "-n", HISTORY_LIMIT, LOG_FORMAT, "--date=short", "--", &name]).unwrap_or_default();
+        // Oldest first; keep only commits that changed the value
+        for commit in parse_commits(&raw).into_iter().rev() {
+            let src = git::git(dir, &["show", &format!("{}:./{name}", commit.id)]).unwrap_or_default();
+            let value = option_value(&src, option);
+            if history.last().is_none_or(|(_, v)| *v != value) {
+                history.push((Some(commit), value));
+            }
+        }
+        let current = std::fs::read_to_string(&path).ok().and_then(|src| option_value(&src, option));
+        if history.last().is_none_or(|(_, v)| *v != current) {
+            history.push((None, current));
+        }
+    }
+
+    Ok(Blame { path: line.path.clone(), line: line.line, text: line.text.clone(), option: line.option.clone(), last, history })
+}
+
+fn rel(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root).unwrap_or(path).display().to_string()
+}
+
+impl Blame {
+    pub fn to_text(&self, root: &Path) -> String {
+        let mut out = format!("{}:{}  {}\n\n", rel(root, &self.path), self.line, self.text);
+        match &self.last {
+            Some(c) => out.push_str(&format!("Last changed in {}  {}  {}\n    {}\n", c.id, c.author, c.date, c.summary)),
+            None => out.push_str("Not committed yet\n"),
+        }
+
+        if let Some(option) = &self.option {
+            out.push_str(&format!("\nValues of {option} (oldest first):\n"));
+            for (commit, value) in &self.history {
+                let value = value.as_deref().unwrap_or("(not set)");
+                match commit {
+                    Some(c) => out.push_str(&format!("    {}  {}  {:<24} {}  ({})\n", c.date, c.id, value, c.summary, c.author)),
+                    None => out.push_str(&format!("    {:<10}  {:<7}  {value}\n", "now", "")),
+                }
+            }
+        }
+        out
+    }
+}
@@ -0,0 +1,226 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use hyprconf::xdg;
+
+use crate::{
+    cli::BookmarksArgs,
+    edit,
+    model::{ConfigEntry, ConfigLine},
+    tsv::{escape, unescape},
+    ui::Picker,
+    watch::LiveEntry,
+};
+
+/// Longest label derived from a line's text when none is given.
+const LABEL_LEN: usize = 40;
+
+/// A line worth jumping back to. `text` is the line's trimmed content as last seen; the
+/// bookmark follows it when lines are added or removed above, or an option's value changes.
+#[derive(Debug, Clone)]
+pub struct Bookmark {
+    pub path: PathBuf,
+    pub line: usize,
+    pub label: String,
+    pub text: String,
+    /// The text is no longer in the file; `line` is where it was last seen
+    pub lost: bool,
+}
+
+/// Pinned entries and line bookmarks, kept in `$XDG_STATE_HOME/hyprconf/bookmarks`.
+#[derive(Debug, Clone, Default)]
+pub struct Bookmarks {
+    pub pins: Vec<PathBuf>,
+    pub marks: Vec<Bookmark>,
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Left-hand side of a `key = value` line.
+fn key(text: &str) -> Option<&str> {
+    text.split_once('=').map(|(k, _)| k.trim()).filter(|k| !k.is_empty())
+}
+
+impl Bookmark {
+    /// Line the bookmarked text is on now, with its current text: the stored line while it
+    /// still holds the text, else the nearest line that does, else (for a changed value) the
+    /// nearest line setting the same key. None when the text is gone.
+    pub fn anchor(&self) -> Option<(usize, String)> {
+        let src = fs::read_to_string(&self.path).ok()?;
+        let lines: Vec<&str> = src.lines().map(str::trim).collect();
+        if lines.get(self.line.wrapping_sub(1)) == Some(&self.text.as_str()) {
+            return Some((self.line, self.text.clone()));
+        }
+        let nearest = |matches: &dyn Fn(&str) -> bool| {
+            (1..=lines.len()).filter(|n| matches(lines[n - 1])).min_by_key(|n| n.abs_diff(self.line))
+        };
+        let line = nearest(&|l| l == self.text).or_else(|| {
+            let k = key(&self.text)?;
+            nearest(&|l| key(l) == Some(k))
+        })?;
+        Some((line, lines[line - 1].to_string()))
+    }
+}
+
+impl Bookmarks {
+    pub fn path() -> Result<PathBuf> {
+        Ok(xdg::dir("XDG_STATE_HOME", ".local/state")?.join("hyprconf").join("bookmarks"))
+    }
+
+    /// Load pins and bookmarks; a missing or unreadable file has none.
+    pub fn load() -> Self {
+        let mut out = Self::default();
+        let Ok(src) = Self::path().and_then(|p| Ok(fs::read_to_string(p)?)) else { return out };
+        for line in src.lines() {
+            let cols: Vec<&str> = line.split('\t').collect();
+            match cols[..] {
+                ["pin", path] => out.pins.push(PathBuf::from(unescape(path))),
+                ["mark", path, line, label, text] => {
+                    let Ok(line) = line.parse() else { continue };
+                    let (path, label, text) = (PathBuf::from(unescape(path)), unescape(label), unescape(text));
+                    out.marks.push(Bookmark { path, line, label, text, lost: false });
+                }
+                _ => {}
+            }
+        }
+        out
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let file = Self::path()?;
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        let mut out = String::new();
+        for p in &self.pins {
+            out.push_str(&format!("pin\t{}\n", escape(&p.to_string_lossy())));
+        }
+        for m in &self.marks {
+            out.push_str(&format!(
+                "mark\t{}\t{}\t{}\t{}\n",
+                escape(&m.path.to_string_lossy()),
+                m.line,
+                escape(&m.label),
+                escape(&m.text),
+            ));
+        }
+        fs::write(&file, out).with_context(|| format!("writing {}", file.display()))
+    }
+
+    pub fn is_pinned(&self, path: &Path) -> bool {
+        self.pins.contains(&canonical(path))
+    }
+
+    /// Move every bookmark to where its text is now, saving if any moved or changed.
+    pub fn reanchor(&mut self) -> Result<()> {
+        let mut moved = false;
+        for m in &mut self.marks {
+            match m.anchor() {
+                Some((line, text)) => {
+                    moved |= line != m.line || text != m.text;
+                    (m.line, m.text) = (line, text);
+                    m.lost = false;
+                }
+                None => m.lost = true,
+            }
+        }
+        if moved { self.save() } else { Ok(()) }
+    }
+}
+
+/// Flag pinned entries and move them to the front, keeping their order otherwise.
+pub fn pin_first(entries: &mut [LiveEntry], bookmarks: &Bookmarks) {
+    for e in entries.iter_mut() {
+        e.pinned = bookmarks.is_pinned(&e.entry.path);
+    }
+    entries.sort_by_key(|e| !e.pinned);
+}
+
+/// ctrl-t in the file picker: pin or unpin the entry.
+pub fn toggle_pin(entry: &ConfigEntry) -> Result<()> {
+    let mut bookmarks = Bookmarks::load();
+    let path = canonical(&entry.path);
+    match bookmarks.pins.iter().position(|p| *p == path) {
+        Some(i) => {
+            bookmarks.pins.remove(i);
+        }
+        None => bookmarks.pins.push(path),
+    }
+    bookmarks.save()
+}
+
+fn prompt(question: &str) -> Result<String> {
+    print!("{question}");
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(answer.trim().to_string())
+}
+
+/// ctrl-t in line-search mode: bookmark the line under a prompted label, or remove its
+/// bookmark. Returns a message to show.
+pub fn toggle_bookmark(line: &ConfigLine) -> Result<String> {
+    let mut bookmarks = Bookmarks::load();
+    bookmarks.reanchor()?;
+    let path = canonical(&line.path);
+    if let Some(i) = bookmarks.marks.iter().position(|m| m.path == path && m.line == line.line && !m.lost) {
+        let removed = bookmarks.marks.remove(i);
+        bookmarks.save()?;
+        return Ok(format!("removed bookmark \"{}\"", removed.label));
+    }
+
+    let text = line.text.trim().to_string();
+    let default = line.option.clone().unwrap_or_else(|| text.chars().take(LABEL_LEN).collect());
+    let answer = prompt(&format!("label for {}:{} [{default}]: ", line.path.display(), line.line))?;
+    let label = if answer.is_empty() { default } else { answer };
+    bookmarks.marks.push(Bookmark { path, line: line.line, label: label.clone(), text, lost: false });
+    bookmarks.save()?;
+    Ok(format!("bookmarked \"{label}\""))
+}
+
+fn bookmark_line(m: &Bookmark, root: &Path) -> String {
+    let root = canonical(root);
+    let path = m.path.strip_prefix(&root).unwrap_or(&m.path);
+    let lost = if m.lost { "  (text not found)" } else { "" };
+    format!("{:<24} {}:{}  {}{lost}", m.label, path.display(), m.line, m.text)
+}
+
+/// `hyprconf bookmarks`: jump to a bookmarked line, list bookmarks, or remove one.
+pub fn run(args: &BookmarksArgs, root: &Path, entries: &[ConfigEntry], picker: &Picker, editor: Option<&str>) -> Result<()> {
+    let mut bookmarks = Bookmarks::load();
+    bookmarks.reanchor()?;
+
+    if let Some(label) = &args.remove {
+        let before = bookmarks.marks.len();
+        bookmarks.marks.retain(|m| m.label != *label);
+        if bookmarks.marks.len() == before {
+            bail!("no bookmark labelled \"{label}\"");
+        }
+        return bookmarks.save();
+    }
+
+    if bookmarks.marks.is_empty() {
+        println!("no bookmarks yet; press ctrl-t on a line in `hyprconf --lines` to add one");
+        return Ok(());
+    }
+    let lines: Vec<String> = bookmarks.marks.iter().map(|m| bookmark_line(m, root)).collect();
+
+    if args.list {
+        for l in &lines {
+            println!("{l}");
+        }
+        return Ok(());
+    }
+
+    if let Some(i) = picker.pick_index(&lines)? {
+        let m = &bookmarks.marks[i];
+        edit::open_path(editor, entries, &m.path, Some(m.line), root)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,402 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Read, Write},
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use hyprconf::xdg;
+use sha2::{Digest, Sha256};
+use similar::{ChangeTag, TextDiff};
+
+use crate::{
+    cli::{ExportArgs, ImportArgs},
+    dotfiles, history,
+    model::{Category, ConfigEntry},
+    scan,
+    tsv::{escape, unescape},
+};
+
+/// Bump when the manifest layout changes.
+const FORMAT: &str = "hyprconf-bundle 1";
+const MANIFEST: &str = "manifest.tsv";
+/// Files are stored under this directory in the archive, at their path relative to the root.
+const FILES: &str = "files";
+
+/// One file of a bundle, as listed in the manifest.
+#[derive(Debug, Clone)]
+struct BundleFile {
+    rel: PathBuf,
+    category: Category,
+    alias: String,
+    description: Option<String>,
+    sha256: String,
+    mode: u32,
+    data: Vec<u8>,
+}
+
+#[derive(Debug, Default)]
+struct Bundle {
+    files: Vec<BundleFile>,
+    /// Include graph: `(file, sourced file)`; the target is relative to the root unless it
+    /// lies outside it
+    sources: Vec<(PathBuf, PathBuf)>,
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(unix)]
+fn mode(path: &Path) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).map(|m| m.permissions().mode() & 0o777).unwrap_or(0o644)
+}
+
+#[cfg(not(unix))]
+fn mode(_path: &Path) -> u32 {
+    0o644
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: u32) -> io::Result<()> {
+    Ok(())
+}
+
+/// Relative, without `..`: safe to join onto the target root.
+fn is_plain(rel: &Path) -> bool {
+    rel.components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+fn manifest(bundle: &Bundle) -> String {
+    let mut out = format!("{FORMAT}\n");
+    for f in &bundle.files {
+        let category = f.category.name();
+        out.push_str(&format!(
+            "file\t{}\t{category}\t{}\t{}\t{}\n",
+            escape(&f.rel.to_string_lossy()),
+            escape(&f.alias),
+            escape(f.description.as_deref().unwrap_or("")),
+            f.sha256,
+        ));
+    }
+    for (from, to) in &bundle.sources {
+        out.push_str(&format!("source\t{}\t{}\n", escape(&from.to_string_lossy()), escape(&to.to_string_lossy())));
+    }
+    out
+}
+
+fn append(tar: &mut tar::Builder<GzEncoder<fs::File>>, name: &Path, mode: u32, data: &[u8]) -> io::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(mode);
+    header.set_mtime(history::now());
+    header.set_cksum();
+    tar.append_data(&mut header, name, data)
+}
+
+/// `hyprconf export`: every scanned file under the root, plus a manifest with categories,
+/// aliases, descriptions, checksums and the include graph.
+pub fn export(args: &ExportArgs, root: &Path, entries: &[ConfigEntry]) -> Result<()> {
+    if args.output.exists() && !args.force {
+        bail!("{} exists; pass --force to replace it", args.output.display());
+    }
+
+    let mut bundle = Bundle::default();
+    for e in entries {
+        let Ok(rel) = e.path.strip_prefix(root) else { continue };
+        // Broken symlinks are scanned too
+        let data = match fs::read(&e.path) {
+            Ok(data) => data,
+            Err(err) => {
+                eprintln!("warning: skipping {}: {err}", rel.display());
+                continue;
+            }
+        };
+        if e.category != Category::Scripts {
+            for to in scan::sources(&e.path) {
+                let to = to.strip_prefix(root).map(Path::to_path_buf).unwrap_or(to);
+                bundle.sources.push((rel.to_path_buf(), to));
+            }
+        }
+        bundle.files.push(BundleFile {
+            rel: rel.to_path_buf(),
+            category: e.category,
+            alias: e.alias.clone(),
+            description: e.description.clone(),
+            sha256: sha256(&data),
+            mode: mode(&e.path),
+            data,
+        });
+    }
+    if bundle.files.is_empty() {
+        bail!("no config files found under {}", root.display());
+    }
+
+    let file = fs::File::create(&args.output).with_context(|| format!("creating {}", args.output.display()))?;
+    let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    append(&mut tar, Path::new(MANIFEST), 0o644, manifest(&bundle).as_bytes())?;
+    for f in &bundle.files {
+        append(&mut tar, &Path::new(FILES).join(&f.rel), f.mode, &f.data)?;
+    }
+    tar.into_inner()?.finish()?;
+    println!("wrote {} file(s) to {}", bundle.files.len(), args.output.display());
+    Ok(())
+}
+
+/// Read and verify a bundle: every manifest entry must be present with a matching checksum.
+fn read_bundle(path: &Path) -> Result<Bundle> {
+    let file = fs::File::open(path).with_context(|| format!("opening {}", path.display()))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    let mut stored: HashMap<PathBuf, (u32, Vec<u8>)> = HashMap::new();
+    for entry in archive.entries().with_context(|| format!("reading {}", path.display()))? {
+        let mut entry = entry.with_context(|| format!("reading {}", path.display()))?;
+        let name = entry.path()?.into_owned();
+        // The archive is untrusted: drop setuid/setgid/sticky and group/other write bits.
+        let mode = entry.header().mode().unwrap_or(0o644) & 0o755;
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+        stored.insert(name, (mode, data));
+    }
+
+    let src = stored
+        .get(Path::new(MANIFEST))
+        .map(|(_, data)| String::from_utf8_lossy(data).into_owned())
+        .with_context(|| format!("{} is not a hyprconf bundle (no {MANIFEST})", path.display()))?;
+    let mut lines = src.lines();
+    if lines.next() != Some(FORMAT) {
+        bail!("{} was written by an incompatible hyprconf version", path.display());
+    }
+
+    let mut bundle = Bundle::default();
+    for line in lines {
+        let cols: Vec<&str> = line.split('\t').collect();
+        match cols[..] {
+            ["file", rel, category, alias, description, checksum] => {
+                let rel = PathBuf::from(unescape(rel));
+                if !is_plain(&rel) {
+                    bail!("refusing to import {}: not a plain relative path", rel.display());
+                }
+                let (mode, data) = stored
+                    .remove(&Path::new(FILES).join(&rel))
+                    .with_context(|| format!("{} is listed in the manifest but missing", rel.display()))?;
+                if sha256(&data) != checksum {
+                    bail!("{}: checksum mismatch; the bundle is damaged", rel.display());
+                }
+                let category: Category = category.parse()?;
+                let description = Some(unescape(description)).filter(|d| !d.is_empty());
+                bundle.files.push(BundleFile {
+                    rel,
+                    category,
+                    alias: unescape(alias),
+                    description,
+                    sha256: checksum.to_string(),
+                    mode,
+                    data,
+                });
+            }
+            ["source", from, to] => bundle.sources.push((PathBuf::from(unescape(from)), PathBuf::from(unescape(to)))),
+            _ => bail!("malformed manifest line: {line}"),
+        }
+    }
+    Ok(bundle)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Add,
+    Replace,
+    Same,
+}
+
+/// `+N -M` lines between the file on disk and the bundled one.
+fn line_stats(old: &[u8], new: &[u8]) -> String {
+    let (Ok(old), Ok(new)) = (std::str::from_utf8(old), std::str::from_utf8(new)) else {
+        return "binary".to_string();
+    };
+    let diff = TextDiff::from_lines(old, new);
+    let count = |tag| diff.iter_all_changes().filter(|c| c.tag() == tag).count();
+    format!("+{} -{}", count(ChangeTag::Insert), count(ChangeTag::Delete))
+}
+
+fn print_diff(old: &[u8], new: &[u8], name: &str) {
+    let (old, new) = (String::from_utf8_lossy(old), String::from_utf8_lossy(new));
+    print!("{}", TextDiff::from_lines(&old, &new).unified_diff().header(name, name));
+}
+
+/// The first symlink on the way from `root` to `root/rel`, the file itself included. Writing
+/// through one lands outside the root, e.g. in a stow package.
+fn symlink_in(root: &Path, rel: &Path) -> Option<PathBuf> {
+    let mut path = root.to_path_buf();
+    for c in rel.components() {
+        path.push(c);
+        if fs::symlink_metadata(&path).is_ok_and(|m| m.file_type().is_symlink()) {
+            return Some(path);
+        }
+    }
+    None
+}
+
+/// Whether `target` can be written: the file itself if it exists, else the closest existing
+/// directory above it, where the missing ones would be created.
+fn can_write(target: &Path) -> bool {
+    match target.ancestors().find(|p| fs::symlink_metadata(p).is_ok()) {
+        Some(p) if p == target => !p.is_dir() && dotfiles::writable(p),
+        Some(dir) => dir.is_dir() && dotfiles::writable(dir),
+        None => false,
+    }
+}
+
+enum Answer {
+    Yes,
+    No,
+    All,
+    Quit,
+}
+
+fn ask(action: Action, f: &BundleFile, target: &Path) -> Result<Answer> {
+    let verb = if action == Action::Add { "add" } else { "overwrite" };
+    loop {
+        print!("{verb} {}? [y/N/a/q/d] ", f.rel.display());
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        if io::stdin().read_line(&mut answer)? == 0 {
+            return Ok(Answer::Quit);
+        }
+        match answer.trim() {
+            "y" | "Y" | "yes" => return Ok(Answer::Yes),
+            "a" | "A" | "all" => return Ok(Answer::All),
+            "q" | "Q" | "quit" => return Ok(Answer::Quit),
+            "d" | "D" | "diff" => {
+                let old = fs::read(target).unwrap_or_default();
+                print_diff(&old, &f.data, &f.rel.display().to_string());
+            }
+            _ => return Ok(Answer::No),
+        }
+    }
+}
+
+/// `hyprconf import BUNDLE`: show what the bundle would add or overwrite under `root`, let the
+/// user pick files, and keep a snapshot of every file it replaces.
+pub fn import(args: &ImportArgs, root: &Path) -> Result<()> {
+    let bundle = read_bundle(&args.bundle)?;
+    let mut plan: Vec<(Action, &BundleFile)> = bundle
+        .files
+        .iter()
+        .map(|f| {
+            let action = match fs::read(root.join(&f.rel)) {
+                Err(_) => Action::Add,
+                Ok(old) if sha256(&old) == f.sha256 => Action::Same,
+                Ok(_) => Action::Replace,
+            };
+            (action, f)
+        })
+        .collect();
+    // Files reached through a symlink belong to whatever the link points into
+    plan.retain(|(action, f)| {
+        let Some(link) = symlink_in(root, &f.rel).filter(|_| *action != Action::Same) else { return true };
+        let target = fs::read_link(&link).map(|t| t.display().to_string()).unwrap_or_default();
+        if args.force {
+            eprintln!("warning: {} is written through the symlink {} → {target}", f.rel.display(), link.display());
+            return true;
+        }
+        eprintln!("warning: skipping {}: {} is a symlink to {target} (--force writes through it)", f.rel.display(), link.display());
+        false
+    });
+
+    println!("bundle: {} ({} files)", args.bundle.display(), bundle.files.len());
+    println!("root:   {}", root.display());
+    let width = plan.iter().map(|(_, f)| f.rel.to_string_lossy().len()).max().unwrap_or(0);
+    for (i, (action, f)) in plan.iter().filter(|(a, _)| *a != Action::Same).enumerate() {
+        if i == 0 {
+            println!();
+        }
+        let (mark, note) = match action {
+            Action::Add => ("+", f.description.clone().unwrap_or_default()),
+            _ => ("~", line_stats(&fs::read(root.join(&f.rel)).unwrap_or_default(), &f.data)),
+        };
+        let rel = f.rel.display().to_string();
+        let row = format!("  {mark} {rel:<width$}  {:<9} {:<16} {note}", f.category.to_string(), f.alias);
+        println!("{}", row.trim_end());
+    }
+    let count = |a| plan.iter().filter(|(action, _)| *action == a).count();
+    let (added, replaced, same) = (count(Action::Add), count(Action::Replace), count(Action::Same));
+    println!("\n{added} new, {replaced} overwritten, {same} unchanged");
+
+    if args.dry_run || added + replaced == 0 {
+        return Ok(());
+    }
+
+    let mut chosen: Vec<(Action, &BundleFile)> = Vec::new();
+    let mut all = args.yes;
+    for &(action, f) in plan.iter().filter(|(a, _)| *a != Action::Same) {
+        if !all {
+            match ask(action, f, &root.join(&f.rel))? {
+                Answer::Yes => {}
+                Answer::No => continue,
+                Answer::All => all = true,
+                Answer::Quit => break,
+            }
+        }
+        chosen.push((action, f));
+    }
+    if chosen.is_empty() {
+        println!("nothing imported");
+        return Ok(());
+    }
+    // Fail before touching anything rather than halfway through
+    let denied: Vec<String> = chosen
+        .iter()
+        .map(|(_, f)| root.join(&f.rel))
+        .filter(|t| !can_write(t))
+        .map(|t| format!("  {}", t.display()))
+        .collect();
+    if !denied.is_empty() {
+        bail!("nothing imported; these files can't be written:\n{}", denied.join("\n"));
+    }
+
+    // Snapshot everything that gets replaced before writing anything
+    let snapshot = xdg::dir("XDG_STATE_HOME", ".local/state")?
+        .join("hyprconf")
+        .join("snapshots")
+        .join(history::now().to_string());
+    let mut saved = 0;
+    for (_, f) in chosen.iter().filter(|(a, _)| *a == Action::Replace) {
+        let to = snapshot.join(&f.rel);
+        if let Some(dir) = to.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        fs::copy(root.join(&f.rel), &to).with_context(|| format!("saving {}", to.display()))?;
+        saved += 1;
+    }
+
+    let kept = if saved > 0 { format!("; the replaced files are saved in {}", snapshot.display()) } else { String::new() };
+    for (_, f) in &chosen {
+        let target = root.join(&f.rel);
+        if let Some(dir) = target.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("creating {}{kept}", dir.display()))?;
+        }
+        fs::write(&target, &f.data).with_context(|| format!("writing {}{kept}", target.display()))?;
+        set_mode(&target, f.mode).with_context(|| format!("setting permissions of {}{kept}", target.display()))?;
+    }
+    println!("imported {} file(s) into {}", chosen.len(), root.display());
+    if saved > 0 {
+        println!("{saved} replaced file(s) saved in {}", snapshot.display());
+    }
+
+    // Includes that point at files the user skipped or that only existed on the other machine
+    for (from, to) in &bundle.sources {
+        if chosen.iter().any(|(_, f)| f.rel == *from) && !root.join(to).exists() {
+            eprintln!("warning: {} sources {}, which does not exist here", from.display(), to.display());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,227 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::UNIX_EPOCH,
+};
+
+use crate::{
+    Error, Result,
+    model::{Header, Summary},
+    xdg,
+};
+
+/// Bump when the record layout changes; older files are ignored.
+const FORMAT: &str = "hyprconf-cache 3";
+
+/// What a scan reads from a file's contents.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Meta {
+    pub description: Option<String>,
+    pub header: Header,
+    pub summary: Summary,
+}
+
+/// What we know about a file without reading it again, valid while size and mtime match, or
+/// while the contents hash the same after only the mtime changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub size: u64,
+    pub mtime_ns: u128,
+    /// FNV-1a of the contents
+    pub hash: u64,
+    pub meta: Meta,
+}
+
+/// Per-root scan cache in `$XDG_CACHE_HOME/hyprconf/`. Lookups are shared between walker
+/// threads; records for files seen in this scan are collected and written back by `save`.
+#[derive(Debug)]
+pub struct Cache {
+    file: PathBuf,
+    old: HashMap<PathBuf, Record>,
+    seen: Mutex<HashMap<PathBuf, Record>>,
+}
+
+/// Where cache files live: `$XDG_CACHE_HOME/hyprconf`.
+pub fn dir() -> Result<PathBuf> {
+    Ok(xdg::dir("XDG_CACHE_HOME", ".cache")?.join("hyprconf"))
+}
+
+/// FNV-1a; stable across builds, unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |h, b| (h ^ *b as u64).wrapping_mul(0x100000001b3))
+}
+
+/// Make `s` safe for one tab-separated column.
+pub(crate) fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n")
+}
+
+pub(crate) fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => break,
+        }
+    }
+    out
+}
+
+/// Header values are never empty, so an empty column means unset.
+fn opt_column(s: &Option<String>) -> String {
+    escape(s.as_deref().unwrap_or(""))
+}
+
+fn parse_opt(s: &str) -> Option<String> {
+    (!s.is_empty()).then(|| unescape(s))
+}
+
+/// Header list items never contain commas: they are split on them.
+fn list_column(items: &[String]) -> String {
+    escape(&items.join(","))
+}
+
+fn parse_list(s: &str) -> Vec<String> {
+    unescape(s).split(',').filter(|i| !i.is_empty()).map(String::from).collect()
+}
+
+fn summary_column(s: &Summary) -> String {
+    format!("{},{},{},{},{}", s.lines, s.assignments, s.binds, s.variables, s.sources)
+}
+
+fn parse_summary(s: &str) -> Option<Summary> {
+    let counts: Vec<usize> = s.split(',').map(str::parse).collect::<Result<_, _>>().ok()?;
+    let [lines, assignments, binds, variables, sources] = counts[..] else { return None };
+    Some(Summary { lines, assignments, binds, variables, sources })
+}
+
+/// Size and mtime of the file `path` points to; None for missing files and broken links.
+fn stamp(path: &Path) -> Option<(u64, u128)> {
+    let meta = fs::metadata(path).ok()?;
+    let mtime = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+    Some((meta.len(), mtime))
+}
+
+impl Cache {
+    /// Load the cache for `root`; a missing or unreadable cache is simply empty.
+    pub fn load(root: &Path) -> Result<Self> {
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let file = dir()?.join(format!("scan-{:016x}.tsv", fnv1a(root.as_os_str().as_encoded_bytes())));
+        let mut old = HashMap::new();
+        if let Ok(src) = fs::read_to_string(&file)
+            && src.lines().next() == Some(FORMAT)
+        {
+            for line in src.lines().skip(1) {
+                let cols: Vec<&str> = line.split('\t').collect();
+                let [path, size, mtime, hash, has_desc, desc, alias, header_desc, tags, hosts, requires, summary] =
+                    cols[..]
+                else {
+                    continue;
+                };
+                let (Ok(size), Ok(mtime_ns), Ok(hash), Some(summary)) =
+                    (size.parse(), mtime.parse(), u64::from_str_radix(hash, 16), parse_summary(summary))
+                else {
+                    continue;
+                };
+                let description = (has_desc == "1").then(|| unescape(desc));
+                let header = Header {
+                    alias: parse_opt(alias),
+                    description: parse_opt(header_desc),
+                    tags: parse_list(tags),
+                    hosts: parse_list(hosts),
+                    requires: parse_list(requires),
+                };
+                let meta = Meta { description, header, summary };
+                old.insert(PathBuf::from(unescape(path)), Record { size, mtime_ns, hash, meta });
+            }
+        }
+        Ok(Self { file, old, seen: Mutex::default() })
+    }
+
+    /// Cached metadata of `path`, or `read` it from the file's contents and remember the result.
+    pub fn describe(&self, path: &Path, read: impl FnOnce(&[u8]) -> Meta) -> Meta {
+        let stamp = stamp(path);
+        let old = self.old.get(path);
+        if let (Some((size, mtime_ns)), Some(r)) = (stamp, old)
+            && r.size == size
+            && r.mtime_ns == mtime_ns
+        {
+            self.seen.lock().unwrap().insert(path.to_path_buf(), r.clone());
+            return r.meta.clone();
+        }
+        let bytes = fs::read(path).unwrap_or_default();
+        let Some((size, mtime_ns)) = stamp else { return read(&bytes) };
+        let hash = fnv1a(&bytes);
+        // Touched but unchanged, e.g. by a checkout: no need to parse it again
+        let meta = match old {
+            Some(r) if r.hash == hash => r.meta.clone(),
+            _ => read(&bytes),
+        };
+        let record = Record { size, mtime_ns, hash, meta: meta.clone() };
+        self.seen.lock().unwrap().insert(path.to_path_buf(), record);
+        meta
+    }
+
+    /// Write back the records of this scan (dropping files that are gone) if anything changed.
+    /// Failures are ignored: the cache only saves work.
+    pub fn save(self) {
+        let seen = self.seen.into_inner().unwrap();
+        if seen == self.old {
+            return;
+        }
+        let mut paths: Vec<&PathBuf> = seen.keys().collect();
+        paths.sort();
+        let mut out = format!("{FORMAT}\n");
+        for path in paths {
+            let r = &seen[path];
+            out.push_str(&format!(
+                "{}\t{}\t{}\t{:016x}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                escape(&path.to_string_lossy()),
+                r.size,
+                r.mtime_ns,
+                r.hash,
+                if r.meta.description.is_some() { "1" } else { "0" },
+                escape(r.meta.description.as_deref().unwrap_or("")),
+                opt_column(&r.meta.header.alias),
+                opt_column(&r.meta.header.description),
+                list_column(&r.meta.header.tags),
+                list_column(&r.meta.header.hosts),
+                list_column(&r.meta.header.requires),
+                summary_column(&r.meta.summary),
+            ));
+        }
+        if let Some(parent) = self.file.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        // Write to a temporary file first so concurrent runs never see half a cache
+        let tmp = self.file.with_extension(format!("tmp{}", std::process::id()));
+        if fs::write(&tmp, out).is_ok() {
+            let _ = fs::rename(&tmp, &self.file);
+        }
+    }
+}
+
+/// Remove every cache file; returns how many were removed.
+pub fn clear() -> Result<usize> {
+    let dir = dir()?;
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(Error::io("listing", dir, e)),
+    };
+    let mut removed = 0;
+    for path in entries.flatten().map(|e| e.path()).filter(|p| p.is_file()) {
+        fs::remove_file(&path).map_err(|e| Error::io("removing", &path, e))?;
+        removed += 1;
+    }
+    Ok(removed)
+}
@@ -0,0 +1,20 @@
+use anyhow::{Result, bail};
+
+use crate::{
+    lint::{self, Severity},
+    model::ConfigEntry,
+    schema::Schema,
+};
+
+pub fn run(entries: &[ConfigEntry], schema: &Schema) -> Result<()> {
+    let diags = lint::check(entries, schema);
+    for d in &diags {
+        println!("{d}");
+    }
+    eprintln!("checked against the option schema for Hyprland {}", schema.version);
+    let errors = diags.iter().filter(|d| d.severity == Severity::Error).count();
+    if errors > 0 {
+        bail!("{errors} error(s), {} warning(s)", diags.len() - errors);
+    }
+    Ok(())
+}
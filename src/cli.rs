@@ -1,9 +1,18 @@
-use std::{env, path::PathBuf};
+use std::path::PathBuf;
 
-use anyhow::{Context, Result};
-use clap::Parser;
+use anyhow::Result;
+use clap::{
+    Args, Parser, Subcommand,
+    builder::{PossibleValuesParser, TypedValueParser},
+};
+use hyprconf::xdg;
 
-use crate::model::Category;
+use crate::{model::Category, palette::Notation};
+
+/// `--category` values. Category comes from the library, which doesn't depend on clap.
+fn category_parser() -> impl TypedValueParser<Value = Category> {
+    PossibleValuesParser::new(Category::ALL.map(Category::name)).map(|name| name.parse().expect("a possible value"))
+}
 
 #[derive(Parser, Debug)]
 #[command(
@@ -16,10 +25,14 @@ pub struct Cli {
     #[arg(short, long, value_name = "DIR")]
     pub root: Option<PathBuf>,
 
-    /// Pre-filter to a specific category
-    #[arg(long, value_enum)]
+    /// Pre-filter to a specific category; the picker opens on its tab
+    #[arg(long, value_parser = category_parser())]
     pub category: Option<Category>,
 
+    /// Only show files with this `# @tags:` tag
+    #[arg(long, value_name = "TAG")]
+    pub tag: Option<String>,
+
     /// Editor to open the file with (default: hx)
     #[arg(long, value_name = "CMD")]
     pub editor: Option<String>,
@@ -32,6 +45,248 @@ pub struct Cli {
     /// Disable per-line segment colors (category/alias/description coloring)
     #[arg(long)]
     pub no_seg_colors: bool,
+
+    /// Search individual lines of every file instead of whole files
+    #[arg(short, long)]
+    pub lines: bool,
+
+    /// Reopen the most recently opened file, at its last known line
+    #[arg(long, conflicts_with = "lines")]
+    pub last: bool,
+
+    /// Directory levels to scan below conf.d/, themes/, plugins/ and scripts/ (default: 3)
+    #[arg(long, value_name = "N", global = true)]
+    pub max_depth: Option<usize>,
+
+    /// Keep the picker up to date as files are added, changed or removed
+    #[arg(short, long)]
+    pub watch: bool,
+
+    /// Read every file instead of reusing cached descriptions
+    #[arg(long, global = true)]
+    pub no_cache: bool,
+
+    /// Include hidden files and directories when scanning
+    #[arg(long, global = true)]
+    pub hidden: bool,
+
+    /// hyprconf settings file (default: ~/.config/hyprconf/hyprconf.conf)
+    #[arg(long, value_name = "FILE", global = true)]
+    pub config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Show the colors of a theme (or the whole config) as swatches
+    Palette(PaletteArgs),
+    /// Reformat config files in place (indentation, `=` spacing, blank lines)
+    Fmt(FmtArgs),
+    /// Validate options against the option schema
+    Check,
+    /// Show docs, every definition in load order, variables and the live value of an option
+    Explain(ExplainArgs),
+    /// Compare the effective options, monitors, env, binds, rules and autostart commands of two config roots
+    Diff(DiffArgs),
+    /// Rewrite deprecated syntax for newer Hyprland releases
+    Migrate(MigrateArgs),
+    /// Write every scanned file and a manifest to a .tar.gz bundle
+    Export(ExportArgs),
+    /// Preview a bundle against the config root and pick which files to copy in
+    Import(ImportArgs),
+    /// Show hypridle listeners as a timeline and flag problems
+    Idle,
+    /// List and check hyprpaper wallpapers, or set one for a monitor
+    Wallpaper(WallpaperArgs),
+    /// Outline and validate hyprlock widgets and jump to one (problems are marked with !)
+    Lock(LockArgs),
+    /// Jump to a bookmarked line (ctrl-t in --lines mode adds one)
+    Bookmarks(BookmarksArgs),
+    /// List recently opened files and reopen one
+    Recent(RecentArgs),
+    /// List profiles or switch the active one
+    Profile(ProfileArgs),
+    /// Manage the scan cache
+    Cache(CacheArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct PaletteArgs {
+    /// Theme alias or file to extract colors from (default: the whole config)
+    pub theme: Option<String>,
+
+    /// Print swatches to stdout instead of opening the picker
+    #[arg(long)]
+    pub list: bool,
+
+    /// Convert a color or gradient value instead of scanning, e.g. "0xff89b4fa"
+    #[arg(long, value_name = "COLOR")]
+    pub convert: Option<String>,
+
+    /// Notation to convert to (default: print every notation)
+    #[arg(long, value_enum)]
+    pub to: Option<Notation>,
+}
+
+#[derive(Args, Debug)]
+pub struct FmtArgs {
+    /// Files to format (default: every scanned config file)
+    pub files: Vec<PathBuf>,
+
+    /// Don't write anything; print a diff and fail if any file would change
+    #[arg(long)]
+    pub check: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ExplainArgs {
+    /// Option path, e.g. decoration:blur:size
+    pub option: String,
+
+    /// Print JSON instead of text
+    #[cfg(feature = "serde")]
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    /// Config root to compare from
+    pub a: PathBuf,
+
+    /// Config root to compare to
+    pub b: PathBuf,
+
+    /// Print JSON instead of text
+    #[cfg(feature = "serde")]
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct MigrateArgs {
+    /// Only apply rules up to this Hyprland version (default: all)
+    #[arg(long, value_name = "VERSION")]
+    pub to: Option<String>,
+
+    /// Apply without asking for confirmation
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    /// Bundle to write
+    #[arg(default_value = "hyprconf-bundle.tar.gz")]
+    pub output: PathBuf,
+
+    /// Replace the bundle if it exists
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+    /// Bundle written by `hyprconf export`
+    pub bundle: PathBuf,
+
+    /// Only show what would be added or overwritten
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Copy every file without asking
+    #[arg(short, long)]
+    pub yes: bool,
+
+    /// Also write files whose path in the root goes through a symlink (e.g. into a stow
+    /// package); they are skipped otherwise
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct WallpaperArgs {
+    #[command(subcommand)]
+    pub command: Option<WallpaperCommand>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum WallpaperCommand {
+    /// Pick an image and make it the wallpaper for a monitor
+    Set {
+        /// Monitor name, e.g. DP-1 (default: all monitors)
+        #[arg(default_value = "")]
+        monitor: String,
+
+        /// Directory to start picking in; subdirectories can be browsed (default: the current
+        /// wallpaper's directory)
+        #[arg(long, value_name = "DIR")]
+        dir: Option<PathBuf>,
+
+        /// Apply through hyprpaper's socket without asking
+        #[arg(long)]
+        apply: bool,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct CacheArgs {
+    #[command(subcommand)]
+    pub command: CacheCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CacheCommand {
+    /// Remove every cached entry
+    Clear,
+}
+
+#[derive(Args, Debug)]
+pub struct LockArgs {
+    /// Print the outline and any problems instead of opening the picker
+    #[arg(long)]
+    pub list: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct BookmarksArgs {
+    /// Print bookmarks instead of opening the picker
+    #[arg(long)]
+    pub list: bool,
+
+    /// Remove the bookmarks with this label
+    #[arg(long, value_name = "LABEL")]
+    pub remove: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct RecentArgs {
+    /// Number of files to show
+    #[arg(short = 'n', long, default_value_t = 20)]
+    pub limit: usize,
+
+    /// Print the list instead of opening the picker
+    #[arg(long)]
+    pub list: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ProfileArgs {
+    #[command(subcommand)]
+    pub command: ProfileCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ProfileCommand {
+    /// Show the profiles; `*` marks the active one
+    List,
+    /// Write the profile's modules and themes to profile.conf
+    Switch {
+        /// Profile to switch to (default: the one listing this host)
+        name: Option<String>,
+    },
 }
 
 impl Cli {
@@ -44,13 +299,6 @@ impl Cli {
 }
 
 fn default_root_from_xdg() -> Result<PathBuf> {
-    // Prefer XDG_CONFIG_HOME if set
-    if let Ok(xdg) = env::var("XDG_CONFIG_HOME")
-        && !xdg.trim().is_empty()
-    {
-        return Ok(PathBuf::from(xdg).join("hypr"));
-    }
-    // Fallback to ~/.config/hypr
-    let home = env::var("HOME").context("HOME not set; cannot resolve default root")?;
-    Ok(PathBuf::from(home).join(".config").join("hypr"))
+    // Prefer XDG_CONFIG_HOME if set, else ~/.config/hypr
+    Ok(xdg::dir("XDG_CONFIG_HOME", ".config")?.join("hypr"))
 }
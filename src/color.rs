@@ -0,0 +1,113 @@
+//! Hyprland color values: `rgba(...)`, `rgb(...)`, `0xAARRGGBB` and gradients of them.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+/// A single color or a gradient (`col1 col2 ... [angle]deg`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorValue {
+    pub stops: Vec<Rgba>,
+    pub angle: Option<u32>,
+}
+
+fn hex_byte(s: &str) -> Option<u8> {
+    u8::from_str_radix(s, 16).ok()
+}
+
+fn parse_hex_rgb(hex: &str, with_alpha: bool) -> Option<Rgba> {
+    let want = if with_alpha { 8 } else { 6 };
+    if hex.len() != want || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(Rgba {
+        r: hex_byte(&hex[0..2])?,
+        g: hex_byte(&hex[2..4])?,
+        b: hex_byte(&hex[4..6])?,
+        a: if with_alpha { hex_byte(&hex[6..8])? } else { 0xff },
+    })
+}
+
+fn parse_decimal(args: &str, with_alpha: bool) -> Option<Rgba> {
+    let parts: Vec<&str> = args.split(',').map(str::trim).collect();
+    if parts.len() != if with_alpha { 4 } else { 3 } {
+        return None;
+    }
+    let chan = |s: &str| s.parse::<u8>().ok();
+    let a = if with_alpha {
+        let f = parts[3].parse::<f32>().ok()?;
+        if !(0.0..=1.0).contains(&f) {
+            return None;
+        }
+        (f * 255.0).round() as u8
+    } else {
+        0xff
+    };
+    Some(Rgba { r: chan(parts[0])?, g: chan(parts[1])?, b: chan(parts[2])?, a })
+}
+
+/// Parse one color token in any notation Hyprland understands.
+pub fn parse_color(token: &str) -> Option<Rgba> {
+    let t = token.trim();
+    if let Some(hex) = t.strip_prefix("0x").or_else(|| t.strip_prefix("0X")) {
+        let c = parse_hex_rgb(hex, true)?;
+        // 0xAARRGGBB: rotate alpha from the front
+        return Some(Rgba { r: c.g, g: c.b, b: c.a, a: c.r });
+    }
+    if let Some(args) = t.strip_prefix("rgba(").and_then(|s| s.strip_suffix(')')) {
+        return parse_hex_rgb(args.trim(), true).or_else(|| parse_decimal(args, true));
+    }
+    if let Some(args) = t.strip_prefix("rgb(").and_then(|s| s.strip_suffix(')')) {
+        return parse_hex_rgb(args.trim(), false).or_else(|| parse_decimal(args, false));
+    }
+    // hyprlock and friends also accept plain #RRGGBB / #RRGGBBAA
+    if let Some(hex) = t.strip_prefix('#') {
+        return parse_hex_rgb(hex, false).or_else(|| parse_hex_rgb(hex, true));
+    }
+    None
+}
+
+/// Split a value into tokens, keeping `rgba(r, g, b, a)` together.
+fn color_tokens(value: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut cur = String::new();
+    let mut depth = 0usize;
+    for c in value.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            c if c.is_whitespace() && depth == 0 => {
+                if !cur.is_empty() {
+                    out.push(std::mem::take(&mut cur));
+                }
+                continue;
+            }
+            _ => {}
+        }
+        cur.push(c);
+    }
+    if !cur.is_empty() {
+        out.push(cur);
+    }
+    out
+}
+
+/// Parse a whole value as a color or gradient; anything else yields `None`.
+pub fn parse_color_value(value: &str) -> Option<ColorValue> {
+    let mut stops = Vec::new();
+    let mut angle = None;
+    for tok in color_tokens(value) {
+        if let Some(c) = parse_color(&tok) {
+            stops.push(c);
+        } else if let Some(deg) = tok.strip_suffix("deg").and_then(|d| d.parse::<u32>().ok()) {
+            angle = Some(deg);
+        } else {
+            return None;
+        }
+    }
+    if stops.is_empty() { None } else { Some(ColorValue { stops, angle }) }
+}
@@ -0,0 +1,150 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use hyprconf::xdg;
+
+use crate::{
+    parse::{self, LineKind},
+    scan::ScanOptions,
+};
+
+/// Options for `hyprconf fmt`, from the `fmt { ... }` section.
+#[derive(Debug, Clone)]
+pub struct FmtOptions {
+    /// Spaces per nesting level inside `{}` blocks
+    pub indent: usize,
+    /// Align `=` within runs of consecutive assignments
+    pub align: bool,
+    /// Longest run of blank lines kept
+    pub max_blank_lines: usize,
+}
+
+impl Default for FmtOptions {
+    fn default() -> Self {
+        Self { indent: 4, align: false, max_blank_lines: 1 }
+    }
+}
+
+/// A named set of conf.d modules and themes, from a `profile { ... }` section. Several
+/// sections define several profiles.
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    pub name: String,
+    /// Hostnames the profile is picked for when none was switched to
+    pub hosts: Vec<String>,
+    /// Globs relative to conf.d/, e.g. `laptop/*`; `.conf` may be left out
+    pub modules: Vec<String>,
+    /// Globs relative to themes/
+    pub themes: Vec<String>,
+}
+
+/// hyprconf's own settings. The file uses Hyprland syntax, e.g.
+///
+/// ```text
+/// fmt {
+///     indent = 2
+///     align = true
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    pub fmt: FmtOptions,
+    /// Option schema to use instead of the bundled one (`schema:path`)
+    pub schema_path: Option<PathBuf>,
+    /// Directory walking, from the `scan { ... }` section
+    pub scan: ScanOptions,
+    pub profiles: Vec<Profile>,
+}
+
+impl Settings {
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(xdg::dir("XDG_CONFIG_HOME", ".config")?.join("hyprconf").join("hyprconf.conf"))
+    }
+
+    /// Load settings from `path` (or the default location); a missing default file yields defaults.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let path = match path {
+            Some(p) => p.to_path_buf(),
+            None => {
+                let p = Self::default_path()?;
+                if !p.exists() {
+                    return Ok(Self::default());
+                }
+                p
+            }
+        };
+        let mut settings = Self::default();
+        for line in parse::parse_file(&path)? {
+            let at = || format!("{}:{}", path.display(), line.number);
+            // Each `profile {` starts a new profile; its keys go to the latest one
+            if let LineKind::Open { name, .. } = &line.kind
+                && name == "profile"
+            {
+                if line.depth > 0 {
+                    bail!("{}: `profile {{ }}` blocks can't be nested", at());
+                }
+                settings.profiles.push(Profile::default());
+            }
+            let (Some(key), LineKind::Assign { value, .. }) = (line.option_path(), &line.kind) else {
+                continue;
+            };
+            // `profile:name = x` outside a block would silently extend the previous profile
+            if key.starts_with("profile:") && line.section != "profile" {
+                bail!("{}: {key} must be inside a `profile {{ }}` block", at());
+            }
+            settings.set(&key, value).with_context(at)?;
+        }
+        for (i, p) in settings.profiles.iter().enumerate() {
+            if p.name.is_empty() {
+                bail!("{}: profile {} has no name", path.display(), i + 1);
+            }
+            if settings.profiles[..i].iter().any(|q| q.name == p.name) {
+                bail!("{}: profile {} is defined twice", path.display(), p.name);
+            }
+        }
+        Ok(settings)
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "fmt:indent" => self.fmt.indent = parse_num(value)?,
+            "fmt:align" => self.fmt.align = parse_bool(value)?,
+            "fmt:max_blank_lines" => self.fmt.max_blank_lines = parse_num(value)?,
+            "scan:max_depth" => self.scan.max_depth = parse_num(value)?,
+            "scan:hidden" => self.scan.hidden = parse_bool(value)?,
+            "schema:path" => self.schema_path = Some(xdg::expand_tilde(value)?),
+            _ if let Some(field) = key.strip_prefix("profile:") => {
+                // Profile keys belong to the latest `profile {` block; `load` rejects them elsewhere
+                let Some(profile) = self.profiles.last_mut() else {
+                    bail!("{key} must be inside a `profile {{ }}` block");
+                };
+                match field {
+                    "name" => profile.name = value.to_string(),
+                    "hosts" => profile.hosts.extend(parse_list(value)),
+                    "modules" => profile.modules.extend(parse_list(value)),
+                    "themes" => profile.themes.extend(parse_list(value)),
+                    _ => bail!("unknown setting {key}"),
+                }
+            }
+            _ => bail!("unknown setting {key}"),
+        }
+        Ok(())
+    }
+}
+
+fn parse_num(value: &str) -> Result<usize> {
+    value.parse().with_context(|| format!("expected a number, got {value:?}"))
+}
+
+/// Comma-separated values; repeating the key adds more.
+fn parse_list(value: &str) -> Vec<String> {
+    value.split(',').map(str::trim).filter(|s| !s.is_empty()).map(String::from).collect()
+}
+
+fn parse_bool(value: &str) -> Result<bool> {
+    match value {
+        "true" | "yes" | "on" | "1" => Ok(true),
+        "false" | "no" | "off" | "0" => Ok(false),
+        _ => bail!("expected a boolean, got {value:?}"),
+    }
+}
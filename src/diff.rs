@@ -0,0 +1,324 @@
+use std::{
+    collections::{BTreeMap, HashMap, btree_map::Entry},
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Result, bail};
+
+use crate::{
+    cli::DiffArgs,
+    explain::ordered_lines,
+    lint::is_keyword,
+    parse::{self, LineKind},
+    scan::{ScanOptions, Scanner},
+};
+
+/// What a line contributes to the effective config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Kind {
+    Option,
+    /// `monitor` lines, by output name
+    Monitor,
+    /// `env` variables, by name
+    Env,
+    Bind,
+    /// Window, layer and workspace rules
+    Rule,
+    /// `exec-once` and friends
+    Autostart,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Kind::Option => write!(f, "option"),
+            Kind::Monitor => write!(f, "monitor"),
+            Kind::Env => write!(f, "env"),
+            Kind::Bind => write!(f, "bind"),
+            Kind::Rule => write!(f, "rule"),
+            Kind::Autostart => write!(f, "autostart"),
+        }
+    }
+}
+
+impl Kind {
+    /// Options, monitors, env variables and binds are keyed, so their value can change; rules
+    /// and autostart commands are only ever added or removed.
+    fn keyed(self) -> bool {
+        !matches!(self, Kind::Rule | Kind::Autostart)
+    }
+
+    fn heading(self) -> &'static str {
+        match self {
+            Kind::Option => "options",
+            Kind::Monitor => "monitors",
+            Kind::Env => "env",
+            Kind::Bind => "binds",
+            Kind::Rule => "rules",
+            Kind::Autostart => "autostart",
+        }
+    }
+}
+
+const RULES: &[&str] = &["windowrule", "windowrulev2", "layerrule", "workspace"];
+const AUTOSTART: &[&str] = &["exec-once", "exec", "execr-once", "execr", "exec-shutdown"];
+
+/// One effective setting, with its variables resolved.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Item {
+    pub value: String,
+    #[cfg_attr(feature = "serde", serde(rename = "file"))]
+    pub path: PathBuf,
+    pub line: usize,
+}
+
+/// `a` is the item in the first root, `b` the one in the second.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "change", rename_all = "lowercase"))]
+pub enum Change {
+    Added { b: Item },
+    Removed { a: Item },
+    Changed { a: Item, b: Item },
+}
+
+fn normalize(value: &str) -> String {
+    value.split(',').map(|p| p.split_whitespace().collect::<Vec<_>>().join(" ")).collect::<Vec<_>>().join(", ")
+}
+
+/// `SUPER_SHIFT`, `SHIFT SUPER` and `super+shift` are the same modifiers.
+fn normalize_mods(mods: &str) -> String {
+    let mut parts: Vec<String> =
+        mods.split([' ', '_', '+']).filter(|m| !m.is_empty()).map(str::to_uppercase).collect();
+    parts.sort();
+    parts.dedup();
+    parts.join("+")
+}
+
+/// Key and value of a `bind* = MODS, key, dispatcher, args` line; `bindd` lines carry a
+/// description before the dispatcher.
+fn bind(keyword: &str, value: &str, submap: Option<&str>) -> Option<(String, String)> {
+    let flags = keyword.strip_prefix("bind")?;
+    let mut fields = value.splitn(3, ',').map(str::trim);
+    let (mods, key, mut action) = (fields.next()?, fields.next()?, fields.next().unwrap_or(""));
+    if flags.contains('d') {
+        action = action.split_once(',').map_or("", |(_, rest)| rest.trim());
+    }
+    // `exit,` and `exit` are the same action
+    let action = action.trim_end_matches([',', ' ']);
+    // Letters match either case; key names are kept as written
+    let key = if key.chars().count() == 1 { key.to_uppercase() } else { key.to_string() };
+    let combo = match normalize_mods(mods) {
+        m if m.is_empty() => key,
+        m => format!("{m}+{key}"),
+    };
+    let id = match submap {
+        Some(s) => format!("{combo} (submap {s})"),
+        None => combo,
+    };
+    let value = if flags.is_empty() { action.to_string() } else { format!("{action}  [{keyword}]") };
+    Some((id, value))
+}
+
+/// Key and value of a `NAME, rest` line such as `monitor = DP-1, 2560x1440, 0x0, 1` or
+/// `env = XCURSOR_SIZE, 24`; `unnamed` stands in for a missing name (a monitor line without
+/// one applies to any output).
+fn named(value: &str, unnamed: &str) -> (String, String) {
+    let (name, rest) = value.split_once(',').unwrap_or((value, ""));
+    let name = if name.trim().is_empty() { unnamed } else { name.trim() };
+    (name.to_string(), rest.trim().to_string())
+}
+
+/// The effective settings under `root`: Hyprland's evaluation order with later definitions
+/// winning, and files that are never sourced only filling gaps, as `hyprconf explain` does.
+pub fn effective(root: &Path, opts: &ScanOptions) -> Result<BTreeMap<(Kind, String), Item>> {
+    if !root.is_dir() {
+        bail!("{} is not a directory", root.display());
+    }
+    let entries = Scanner::new(root).options(opts.clone()).scan()?;
+    let lines = ordered_lines(root, &entries);
+
+    let mut vars: HashMap<String, String> = HashMap::new();
+    for (_, line, sourced) in &lines {
+        if let LineKind::Assign { key, value, .. } = &line.kind
+            && let Some(name) = key.strip_prefix('$')
+            && (*sourced || !vars.contains_key(name))
+        {
+            vars.insert(name.to_string(), value.clone());
+        }
+    }
+
+    let mut out: BTreeMap<(Kind, String), Item> = BTreeMap::new();
+    let mut submap: Option<String> = None;
+    let mut last_path: Option<&Path> = None;
+    for (path, line, sourced) in &lines {
+        // Each unsourced file is read on its own, outside any submap
+        if !sourced && last_path != Some(path.as_path()) {
+            submap = None;
+        }
+        last_path = Some(path);
+        let LineKind::Assign { key, value, .. } = &line.kind else { continue };
+        if key.starts_with('$') {
+            continue;
+        }
+        let value = normalize(&parse::resolve_vars(value, &vars));
+        let (kind, id, value) = if key == "submap" {
+            submap = (value != "reset").then_some(value);
+            continue;
+        } else if key == "unbind" {
+            // Drops whatever the combo was bound to so far; later binds add it back
+            if *sourced && let Some((id, _)) = bind("bind", &value, submap.as_deref()) {
+                out.remove(&(Kind::Bind, id));
+            }
+            continue;
+        } else if parse::is_bind_keyword(key) {
+            let Some((id, action)) = bind(key, &value, submap.as_deref()) else { continue };
+            (Kind::Bind, id, action)
+        } else if key == "monitor" {
+            let (name, spec) = named(&value, "(any output)");
+            (Kind::Monitor, name, spec)
+        } else if key == "env" || key == "envd" {
+            let (name, value) = named(&value, "(unnamed)");
+            (Kind::Env, name, value)
+        } else if RULES.contains(&key.as_str()) {
+            (Kind::Rule, format!("{key} = {value}"), value)
+        } else if AUTOSTART.contains(&key.as_str()) {
+            (Kind::Autostart, format!("{key} = {value}"), value)
+        } else if let Some(option) = line.option_path().filter(|_| !is_keyword(key)) {
+            (Kind::Option, option, value)
+        } else {
+            continue;
+        };
+        let item = Item { value, path: path.clone(), line: line.number };
+        match out.entry((kind, id)) {
+            Entry::Vacant(v) => {
+                v.insert(item);
+            }
+            Entry::Occupied(mut o) if *sourced => {
+                o.insert(item);
+            }
+            Entry::Occupied(_) => {}
+        }
+    }
+    Ok(out)
+}
+
+/// Differences from `a` to `b`, ordered by kind and key.
+pub fn diff(a: &BTreeMap<(Kind, String), Item>, b: &BTreeMap<(Kind, String), Item>) -> Vec<((Kind, String), Change)> {
+    let mut out = Vec::new();
+    for (k, old) in a {
+        match b.get(k) {
+            None => out.push((k.clone(), Change::Removed { a: old.clone() })),
+            Some(new) if new.value != old.value => out.push((k.clone(), Change::Changed { a: old.clone(), b: new.clone() })),
+            Some(_) => {}
+        }
+    }
+    for (k, new) in b {
+        if !a.contains_key(k) {
+            out.push((k.clone(), Change::Added { b: new.clone() }));
+        }
+    }
+    out.sort_by(|x, y| x.0.cmp(&y.0));
+    out
+}
+
+/// `path` relative to `root`. Scanned paths start with the root as given, sourced ones are
+/// resolved; try both before falling back to the full path.
+fn rel(root: &Path, path: &Path) -> String {
+    if let Ok(r) = path.strip_prefix(root) {
+        return r.display().to_string();
+    }
+    let resolve = |p: &Path| p.canonicalize().unwrap_or_else(|_| p.to_path_buf());
+    match resolve(path).strip_prefix(resolve(root)) {
+        Ok(r) => r.display().to_string(),
+        Err(_) => path.display().to_string(),
+    }
+}
+
+fn to_text(changes: &[((Kind, String), Change)], root_a: &Path, root_b: &Path) -> String {
+    let at = |root: &Path, i: &Item| format!("{}:{}", rel(root, &i.path), i.line);
+    let show = |kind: Kind, key: &str, value: &str| if kind.keyed() { format!("{key} = {value}") } else { key.to_string() };
+
+    let mut out = format!("a: {}\nb: {}\n", root_a.display(), root_b.display());
+    let mut heading = None;
+    let (mut added, mut removed, mut changed) = (0, 0, 0);
+    for ((kind, key), change) in changes {
+        if heading != Some(*kind) {
+            out.push_str(&format!("\n{}:\n", kind.heading()));
+            heading = Some(*kind);
+        }
+        let line = match change {
+            Change::Added { b } => {
+                added += 1;
+                format!("  + {}  (b {})", show(*kind, key, &b.value), at(root_b, b))
+            }
+            Change::Removed { a } => {
+                removed += 1;
+                format!("  - {}  (a {})", show(*kind, key, &a.value), at(root_a, a))
+            }
+            Change::Changed { a, b } => {
+                changed += 1;
+                format!("  ~ {key} = {} → {}  (a {}, b {})", a.value, b.value, at(root_a, a), at(root_b, b))
+            }
+        };
+        out.push_str(&line);
+        out.push('\n');
+    }
+    if changes.is_empty() {
+        out.push_str("\nno differences\n");
+    } else {
+        out.push_str(&format!("\n{changed} changed, {added} added, {removed} removed\n"));
+    }
+    out
+}
+
+#[cfg(feature = "serde")]
+fn to_json(changes: Vec<((Kind, String), Change)>, root_a: &Path, root_b: &Path) -> Result<String> {
+    #[derive(serde::Serialize)]
+    struct Row {
+        kind: Kind,
+        key: String,
+        #[serde(flatten)]
+        change: Change,
+    }
+    #[derive(serde::Serialize)]
+    struct Report<'a> {
+        a: &'a Path,
+        b: &'a Path,
+        changes: Vec<Row>,
+    }
+
+    // File paths relative to their root, as in the text output
+    let relative = |root: &Path, item: &mut Item| item.path = rel(root, &item.path).into();
+    let changes = changes
+        .into_iter()
+        .map(|((kind, key), mut change)| {
+            match &mut change {
+                Change::Added { b } => relative(root_b, b),
+                Change::Removed { a } => relative(root_a, a),
+                Change::Changed { a, b } => {
+                    relative(root_a, a);
+                    relative(root_b, b);
+                }
+            }
+            Row { kind, key, change }
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&Report { a: root_a, b: root_b, changes })?)
+}
+
+/// `hyprconf diff A B`: what differs between the effective configs of two roots.
+pub fn run(args: &DiffArgs, opts: &ScanOptions) -> Result<()> {
+    let changes = diff(&effective(&args.a, opts)?, &effective(&args.b, opts)?);
+    #[cfg(feature = "serde")]
+    if args.json {
+        println!("{}", to_json(changes, &args.a, &args.b)?);
+        return Ok(());
+    }
+    print!("{}", to_text(&changes, &args.a, &args.b));
+    Ok(())
+}
@@ -0,0 +1,181 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+};
+
+use crate::{
+    model::{ConfigEntry, Link, Manager},
+    xdg,
+};
+
+/// chezmoi source-state attributes, stripped when mapping a source name to its target.
+const CHEZMOI_PREFIXES: &[&str] = &[
+    "create_", "modify_", "remove_", "run_", "once_", "onchange_", "symlink_", "encrypted_", "private_",
+    "readonly_", "empty_", "executable_", "exact_", "literal_",
+];
+
+/// Target name a chezmoi source entry deploys to, e.g. "private_dot_config" -> ".config".
+fn chezmoi_target_name(source: &str) -> String {
+    let mut name = source;
+    while let Some(rest) = CHEZMOI_PREFIXES.iter().find_map(|p| name.strip_prefix(p)) {
+        name = rest;
+    }
+    let name = name.strip_suffix(".tmpl").unwrap_or(name);
+    match name.strip_prefix("dot_") {
+        Some(rest) => format!(".{rest}"),
+        None => name.to_string(),
+    }
+}
+
+fn find_chezmoi_source_dir() -> Option<PathBuf> {
+    let dir = xdg::dir("XDG_DATA_HOME", ".local/share").ok()?.join("chezmoi");
+    if !dir.is_dir() {
+        return None;
+    }
+    // .chezmoiroot moves the source state into a subdirectory
+    match fs::read_to_string(dir.join(".chezmoiroot")) {
+        Ok(sub) => Some(dir.join(sub.trim())),
+        Err(_) => Some(dir),
+    }
+}
+
+/// A stow package tree is marked by `.stow` (stow dir) or `.stow-local-ignore` (package).
+fn in_stow_tree(target: &Path) -> bool {
+    target
+        .ancestors()
+        .skip(1)
+        .any(|dir| dir.join(".stow").exists() || dir.join(".stow-local-ignore").exists())
+}
+
+/// Whether the current user may write `path`: permission bits, ownership and read-only mounts.
+#[cfg(unix)]
+pub fn writable(path: &Path) -> bool {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+    let Ok(c_path) = CString::new(path.as_os_str().as_bytes()) else { return false };
+    // SAFETY: `c_path` is a valid NUL-terminated string for the duration of the call
+    unsafe { libc::access(c_path.as_ptr(), libc::W_OK) == 0 }
+}
+
+#[cfg(not(unix))]
+pub fn writable(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|m| !m.permissions().readonly())
+}
+
+/// Dotfile-manager lookups for one scan: chezmoi's source directory is located once and each
+/// of its directories is listed at most once, however many files are inspected.
+#[derive(Debug, Default)]
+pub struct Resolver {
+    chezmoi_dir: OnceLock<Option<PathBuf>>,
+    /// Source directory -> (target name, source path) of its entries
+    listings: Mutex<HashMap<PathBuf, Vec<(String, PathBuf)>>>,
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn chezmoi_source_dir(&self) -> Option<&Path> {
+        self.chezmoi_dir.get_or_init(find_chezmoi_source_dir).as_deref()
+    }
+
+    /// Entry of the chezmoi source directory `dir` that deploys to the name `want`.
+    fn chezmoi_child(&self, dir: &Path, want: &str) -> Option<PathBuf> {
+        let find = |listing: &[(String, PathBuf)]| listing.iter().find(|(name, _)| name == want).map(|(_, p)| p.clone());
+        if let Some(listing) = self.listings.lock().unwrap().get(dir) {
+            return find(listing);
+        }
+        // Read without holding the lock so walker threads don't wait on each other
+        let listing: Vec<(String, PathBuf)> = fs::read_dir(dir)
+            .map(|read| {
+                read.filter_map(|e| e.ok())
+                    .map(|e| (chezmoi_target_name(&e.file_name().to_string_lossy()), e.path()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let found = find(&listing);
+        self.listings.lock().unwrap().insert(dir.to_path_buf(), listing);
+        found
+    }
+
+    /// Source file chezmoi deploys to `path`, found by walking the source state one component at a time.
+    fn chezmoi_source(&self, path: &Path) -> Option<PathBuf> {
+        let mut dir = self.chezmoi_source_dir()?.to_path_buf();
+        let home = PathBuf::from(env::var_os("HOME")?);
+        let path = std::path::absolute(path).ok()?;
+        for component in path.strip_prefix(&home).ok()?.components() {
+            dir = self.chezmoi_child(&dir, &component.as_os_str().to_string_lossy())?;
+        }
+        Some(dir)
+    }
+
+    fn guess_manager(&self, link: &Link) -> (Option<Manager>, Option<PathBuf>) {
+        let Some(target) = &link.target else {
+            return (None, None);
+        };
+        if target.starts_with("/nix/store") {
+            return (Some(Manager::Nix), None);
+        }
+        // yadm alternates are symlinks to `name##condition`
+        if target.file_name().is_some_and(|n| n.to_string_lossy().contains("##")) {
+            return (Some(Manager::Yadm), None);
+        }
+        if self.chezmoi_source_dir().is_some_and(|dir| target.starts_with(dir)) {
+            return (Some(Manager::Chezmoi), None);
+        }
+        if in_stow_tree(target) {
+            return (Some(Manager::Stow), None);
+        }
+        (None, None)
+    }
+
+    /// Symlink, permission and dotfile-manager details for a scanned file; None for a plain
+    /// writable file nobody manages.
+    pub fn inspect(&self, path: &Path) -> Option<Link> {
+        let meta = fs::symlink_metadata(path).ok()?;
+        let mut link = Link { target: None, broken: false, read_only: false, manager: None, source: None };
+        if meta.file_type().is_symlink() {
+            match fs::canonicalize(path) {
+                Ok(target) => link.target = Some(target),
+                Err(_) => {
+                    let raw = fs::read_link(path).ok()?;
+                    link.broken = true;
+                    link.target = Some(path.parent().map(|p| p.join(&raw)).unwrap_or(raw));
+                }
+            }
+        }
+        if !link.broken {
+            link.read_only = !writable(link.target.as_deref().unwrap_or(path));
+        }
+
+        (link.manager, link.source) = if link.target.is_some() {
+            self.guess_manager(&link)
+        } else if path.starts_with("/nix/store") {
+            (Some(Manager::Nix), None)
+        } else if let Some(src) = self.chezmoi_source(path) {
+            (Some(Manager::Chezmoi), Some(src))
+        } else {
+            (None, None)
+        };
+
+        if link.target.is_none() && !link.read_only && link.manager.is_none() {
+            return None;
+        }
+        Some(link)
+    }
+}
+
+/// Warning to show before opening a file the editor won't be able to save.
+pub fn read_only_warning(entry: &ConfigEntry) -> Option<String> {
+    let link = entry.link.as_ref()?;
+    if !link.read_only || link.source.is_some() {
+        return None;
+    }
+    let hint = match link.manager {
+        Some(Manager::Nix) => "; it is generated by Nix, edit your home-manager configuration instead",
+        _ => "",
+    };
+    Some(format!("{} is read-only{hint}", entry.edit_path().display()))
+}
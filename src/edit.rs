@@ -2,14 +2,32 @@ use std::{env, path::Path, process::Command};
 
 use anyhow::{bail, Context, Result};
 
-pub fn open_in_editor(editor: Option<&str>, path: &Path, _root: &Path) -> Result<()> {
+use crate::{dotfiles, history::History, model::ConfigEntry};
+
+/// Editor arguments that open `path` at `line`, by editor family.
+fn file_args(editor_cmd: &str, path: &Path, line: Option<usize>) -> Vec<String> {
+    let file = path.to_string_lossy().into_owned();
+    let Some(line) = line else { return vec![file] };
+    let name = Path::new(editor_cmd)
+        .file_name()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    match name.as_str() {
+        "hx" | "helix" => vec![format!("{file}:{line}")],
+        "vi" | "vim" | "nvim" | "nano" | "emacs" | "micro" | "kak" | "mg" => vec![format!("+{line}"), file],
+        "code" | "codium" => vec!["--goto".to_string(), format!("{file}:{line}")],
+        _ => vec![file],
+    }
+}
+
+pub fn open_in_editor(editor: Option<&str>, path: &Path, line: Option<usize>, _root: &Path) -> Result<()> {
     let editor_cmd = editor
         .map(|s| s.to_string())
         .or_else(|| env::var("EDITOR").ok())
         .unwrap_or_else(|| "hx".to_string());
 
-    let status = Command::new(editor_cmd)
-        .arg(path)
+    let status = Command::new(&editor_cmd)
+        .args(file_args(&editor_cmd, path, line))
         .status()
         .with_context(|| format!("failed to spawn editor for {}", path.display()))?;
 
@@ -19,3 +37,38 @@ pub fn open_in_editor(editor: Option<&str>, path: &Path, _root: &Path) -> Result
     Ok(())
 }
 
+/// Open a picked entry, at `line` when given, and record it in the history.
+pub fn open_entry(editor: Option<&str>, entry: &ConfigEntry, line: Option<usize>, root: &Path) -> Result<()> {
+    if let Some(link) = &entry.link
+        && link.broken
+        && let Some(target) = &link.target
+    {
+        bail!("{} is a broken symlink to {}", entry.path.display(), target.display());
+    }
+    if !entry.path.exists() {
+        bail!("{} no longer exists", entry.path.display());
+    }
+    if let Some(warning) = dotfiles::read_only_warning(entry) {
+        eprintln!("warning: {warning}");
+    }
+    // History is best effort; it must never keep the editor from opening
+    let _ = History::record(&entry.path, line);
+    // Launch editor on the source-of-truth file
+    open_in_editor(editor, entry.edit_path(), line, root)
+}
+
+/// Open a scanned file by path through its entry, so managed and linked files open at their
+/// source; paths no entry matches open as they are.
+pub fn open_path(editor: Option<&str>, entries: &[ConfigEntry], path: &Path, line: Option<usize>, root: &Path) -> Result<()> {
+    let canonical = path.canonicalize().ok();
+    let entry = entries.iter().find(|e| e.path == path).or_else(|| {
+        canonical.as_ref().and_then(|c| entries.iter().find(|e| e.path.canonicalize().ok().as_ref() == Some(c)))
+    });
+    match entry {
+        Some(e) => open_entry(editor, e, line, root),
+        None => {
+            let _ = History::record(path, line);
+            open_in_editor(editor, path, line, root)
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use std::{fmt, io, path::PathBuf};
+
+/// Errors from the library API.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// `op` ("reading", "removing", ...) failed on `path`
+    Io { op: &'static str, path: PathBuf, source: io::Error },
+    /// `$HOME` is not set, so an XDG directory without its variable can't be resolved
+    NoHome,
+    /// Not the name of a [`Category`](crate::model::Category)
+    UnknownCategory(String),
+    /// A line of an option schema that doesn't follow the format; `path` is None for a schema
+    /// parsed from a string
+    Schema { path: Option<PathBuf>, line: usize, message: String },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl Error {
+    pub(crate) fn io(op: &'static str, path: impl Into<PathBuf>, source: io::Error) -> Self {
+        Error::Io { op, path: path.into(), source }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { op, path, .. } => write!(f, "{op} {}", path.display()),
+            Error::NoHome => write!(f, "HOME not set; cannot resolve XDG directories"),
+            Error::UnknownCategory(name) => write!(f, "unknown category {name:?}"),
+            Error::Schema { path: Some(path), line, message } => write!(f, "{}:{line}: {message}", path.display()),
+            Error::Schema { path: None, line, message } => write!(f, "schema line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::NoHome | Error::UnknownCategory(_) | Error::Schema { .. } => None,
+        }
+    }
+}
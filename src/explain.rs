@@ -0,0 +1,175 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+
+use crate::{
+    cli::ExplainArgs,
+    ipc,
+    model::{Category, ConfigEntry},
+    parse::{self, LineKind},
+    scan::{evaluation_order, load_order},
+    schema::{OptionSpec, Schema},
+};
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Definition {
+    #[cfg_attr(feature = "serde", serde(rename = "file"))]
+    pub path: PathBuf,
+    pub line: usize,
+    pub value: String,
+    /// Whether the file is reached from `hyprland.conf` through `source =`
+    pub sourced: bool,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Explanation {
+    pub option: String,
+    #[cfg_attr(feature = "serde", serde(rename = "schema"))]
+    pub spec: Option<OptionSpec>,
+    /// Every definition in evaluation order; unsourced files come last
+    pub definitions: Vec<Definition>,
+    /// Index into `definitions` of the value Hyprland ends up with
+    pub winner: Option<usize>,
+    /// `$variables` the winning value depends on, with their effective definitions
+    pub variables: BTreeMap<String, Definition>,
+    /// Value reported by a running Hyprland instance
+    pub live: Option<String>,
+}
+
+/// Lines of option-carrying files in evaluation order, followed by scanned files that are
+/// never sourced (flagged `false`).
+pub fn ordered_lines(root: &Path, entries: &[ConfigEntry]) -> Vec<(PathBuf, parse::Line, bool)> {
+    let mut out: Vec<(PathBuf, parse::Line, bool)> =
+        evaluation_order(root).into_iter().map(|(p, l)| (p, l, true)).collect();
+    let sourced = load_order(root);
+    for e in entries {
+        if matches!(e.category, Category::Utility | Category::Scripts) {
+            continue;
+        }
+        let canonical = e.path.canonicalize().unwrap_or_else(|_| e.path.clone());
+        if sourced.contains(&canonical) {
+            continue;
+        }
+        let Ok(lines) = parse::parse_file(&canonical) else { continue };
+        out.extend(lines.into_iter().map(|l| (canonical.clone(), l, false)));
+    }
+    out
+}
+
+fn var_refs(value: &str) -> Vec<String> {
+    value
+        .split('$')
+        .skip(1)
+        .map(|s| s.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect::<String>())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+pub fn explain(root: &Path, entries: &[ConfigEntry], schema: &Schema, option: &str) -> Explanation {
+    let mut definitions = Vec::new();
+    let mut vars: HashMap<String, Definition> = HashMap::new();
+    for (path, line, sourced) in ordered_lines(root, entries) {
+        let LineKind::Assign { key, value, .. } = &line.kind else { continue };
+        let def = || Definition { path: path.clone(), line: line.number, value: value.clone(), sourced };
+        if let Some(name) = key.strip_prefix('$') {
+            // Later sourced definitions win; unsourced files only fill gaps
+            if sourced || !vars.contains_key(name) {
+                vars.insert(name.to_string(), def());
+            }
+        } else if line.option_path().as_deref() == Some(option) {
+            definitions.push(def());
+        }
+    }
+
+    let winner = definitions
+        .iter()
+        .rposition(|d| d.sourced)
+        .or_else(|| definitions.len().checked_sub(1));
+
+    // Follow variable references transitively from the winning value
+    let mut variables: BTreeMap<String, Definition> = BTreeMap::new();
+    let mut pending: Vec<String> = winner.map(|w| var_refs(&definitions[w].value)).unwrap_or_default();
+    while let Some(name) = pending.pop() {
+        if variables.contains_key(&name) {
+            continue;
+        }
+        if let Some(def) = vars.get(&name) {
+            pending.extend(var_refs(&def.value));
+            variables.insert(name, def.clone());
+        }
+    }
+
+    Explanation {
+        option: option.to_string(),
+        spec: schema.get(option).cloned(),
+        definitions,
+        winner,
+        variables,
+        live: ipc::get_option(option),
+    }
+}
+
+fn rel(root: &Path, path: &Path) -> String {
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    path.strip_prefix(&root).unwrap_or(path).display().to_string()
+}
+
+impl Explanation {
+    pub fn to_text(&self, root: &Path) -> String {
+        let mut out = match &self.spec {
+            Some(spec) => spec.doc(),
+            None => format!("{}  (not in the option schema)\n", self.option),
+        };
+
+        out.push_str("\nDefined in load order:\n");
+        if self.definitions.is_empty() {
+            out.push_str("  (not set; the default applies)\n");
+        }
+        for (i, d) in self.definitions.iter().enumerate() {
+            let mark = if Some(i) == self.winner { "*" } else { " " };
+            let note = match (Some(i) == self.winner, d.sourced) {
+                (true, _) => "  (wins)",
+                (false, false) => "  (not sourced)",
+                _ => "",
+            };
+            out.push_str(&format!("  {mark} {}:{}  = {}{note}\n", rel(root, &d.path), d.line, d.value));
+        }
+
+        if !self.variables.is_empty() {
+            out.push_str("\nVariables:\n");
+            for (name, d) in &self.variables {
+                out.push_str(&format!("    ${name} = {}  ({}:{})\n", d.value, rel(root, &d.path), d.line));
+            }
+        }
+
+        if let Some(live) = &self.live {
+            out.push_str(&format!("\nLive value: {live}\n"));
+        }
+        out
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn into_json(mut self, root: &Path) -> Result<String> {
+        // File paths relative to the root, as in the text output
+        for d in self.definitions.iter_mut().chain(self.variables.values_mut()) {
+            d.path = rel(root, &d.path).into();
+        }
+        Ok(serde_json::to_string_pretty(&self)?)
+    }
+}
+
+pub fn run(args: &ExplainArgs, root: &Path, entries: &[ConfigEntry], schema: &Schema) -> Result<()> {
+    let ex = explain(root, entries, schema, &args.option);
+    #[cfg(feature = "serde")]
+    if args.json {
+        println!("{}", ex.into_json(root)?);
+        return Ok(());
+    }
+    print!("{}", ex.to_text(root));
+    Ok(())
+}
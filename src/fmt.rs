@@ -0,0 +1,179 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{Context, Result, bail};
+use similar::TextDiff;
+
+use crate::{
+    cli::FmtArgs,
+    config::FmtOptions,
+    model::{Category, ConfigEntry},
+    parse::{self, LineKind},
+};
+
+fn with_comment(body: String, comment: &Option<String>) -> String {
+    match comment {
+        Some(c) => format!("{body} {c}"),
+        None => body,
+    }
+}
+
+/// Reformat one file's contents. Only whitespace changes: keys, values and comments are
+/// emitted verbatim, so the result is idempotent and parses to the same options.
+pub fn format_str(src: &str, opts: &FmtOptions) -> String {
+    // Drop leading blanks and collapse runs first, so `=` alignment sees the lines it will
+    // be next to in the output
+    let mut lines = Vec::new();
+    let mut blanks = 0usize;
+    for line in parse::parse_str(src) {
+        if matches!(line.kind, LineKind::Blank) {
+            blanks += 1;
+            if lines.is_empty() || blanks > opts.max_blank_lines {
+                continue;
+            }
+        } else {
+            blanks = 0;
+        }
+        lines.push(line);
+    }
+
+    // Key width per line for `=` alignment: runs of assignments at the same depth
+    let mut widths = vec![0usize; lines.len()];
+    if opts.align {
+        let mut start = 0;
+        while start < lines.len() {
+            let mut end = start;
+            while end < lines.len()
+                && matches!(lines[end].kind, LineKind::Assign { .. })
+                && lines[end].depth == lines[start].depth
+            {
+                end += 1;
+            }
+            if end == start {
+                start += 1;
+                continue;
+            }
+            let width = lines[start..end]
+                .iter()
+                .filter_map(|l| match &l.kind {
+                    LineKind::Assign { key, .. } => Some(key.chars().count()),
+                    _ => None,
+                })
+                .max()
+                .unwrap_or(0);
+            widths[start..end].fill(width);
+            start = end;
+        }
+    }
+
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+    for (i, line) in lines.iter().enumerate() {
+        let pad = " ".repeat(line.depth * opts.indent);
+        let text = match &line.kind {
+            LineKind::Blank => String::new(),
+            LineKind::Comment(c) => format!("{pad}{c}"),
+            LineKind::Open { name, comment } => with_comment(format!("{pad}{name} {{"), comment),
+            LineKind::Close { comment } => with_comment(format!("{pad}}}"), comment),
+            LineKind::Assign { key, value, comment } => {
+                let key = format!("{key:<width$}", width = widths[i]);
+                let body = if value.is_empty() { format!("{pad}{key} =") } else { format!("{pad}{key} = {value}") };
+                with_comment(body, comment)
+            }
+            LineKind::Other(raw) => format!("{pad}{raw}"),
+        };
+        out.push(text);
+    }
+    while out.last().is_some_and(|l| l.is_empty()) {
+        out.pop();
+    }
+
+    let mut s = out.join("\n");
+    if !s.is_empty() {
+        s.push('\n');
+    }
+    s
+}
+
+pub fn run(args: &FmtArgs, entries: &[ConfigEntry], opts: &FmtOptions) -> Result<()> {
+    let files: Vec<PathBuf> = if args.files.is_empty() {
+        entries
+            .iter()
+            .filter(|e| e.category != Category::Scripts)
+            .map(|e| e.path.clone())
+            .collect()
+    } else {
+        args.files.clone()
+    };
+
+    let mut changed = 0usize;
+    for path in &files {
+        let src = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        let formatted = format_str(&src, opts);
+        if formatted == src {
+            continue;
+        }
+        changed += 1;
+        if args.check {
+            let name = path.display().to_string();
+            let diff = TextDiff::from_lines(&src, &formatted);
+            print!("{}", diff.unified_diff().header(&name, &name));
+        } else {
+            fs::write(path, &formatted).with_context(|| format!("writing {}", path.display()))?;
+            println!("formatted {}", path.display());
+        }
+    }
+
+    if args.check && changed > 0 {
+        bail!("{changed} file(s) would be reformatted");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSY: &str = "\n\n$mod = SUPER   # main modifier\n$term=kitty\n\
+        general {\n  gaps_in=5\n\tgaps_out   =   10\n\n\n\n\
+        col.active_border = rgba(33ccffee)   # accent\n\
+        decoration {\n blur {\n enabled = true\n }   # end blur\n\n\n  rounding=8\n}\n}# end general\n\
+        \n\n\n\n# binds\nbind=$mod, Return, exec, $term\nbind = $mod, C, exec, notify-send 'C## sharp'\n\n\n";
+
+    #[test]
+    fn format_only_changes_whitespace() {
+        let expected = [
+            "$mod = SUPER # main modifier",
+            "$term = kitty",
+            "general {",
+            "    gaps_in = 5",
+            "    gaps_out = 10",
+            "",
+            "    col.active_border = rgba(33ccffee) # accent",
+            "    decoration {",
+            "        blur {",
+            "            enabled = true",
+            "        } # end blur",
+            "",
+            "        rounding = 8",
+            "    }",
+            "} # end general",
+            "",
+            "# binds",
+            "bind = $mod, Return, exec, $term",
+            "bind = $mod, C, exec, notify-send 'C## sharp'",
+            "",
+        ];
+        assert_eq!(format_str(MESSY, &FmtOptions::default()), expected.join("\n"));
+    }
+
+    #[test]
+    fn format_is_idempotent() {
+        for opts in [
+            FmtOptions::default(),
+            FmtOptions { indent: 2, align: true, max_blank_lines: 0 },
+            FmtOptions { indent: 4, align: true, max_blank_lines: 2 },
+        ] {
+            let once = format_str(MESSY, &opts);
+            assert_eq!(format_str(&once, &opts), once, "{opts:?}");
+        }
+    }
+}
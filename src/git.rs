@@ -0,0 +1,193 @@
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{Context, Result, bail};
+
+use crate::{model::ConfigEntry, watch::LiveEntry};
+
+/// Working-tree state of one file, from `git status --porcelain`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GitStatus {
+    pub staged: bool,
+    pub modified: bool,
+    pub untracked: bool,
+    pub ignored: bool,
+}
+
+impl GitStatus {
+    /// Short marker for the picker line: "S" staged, "M" modified, "?" untracked, "!" ignored.
+    pub fn marker(&self) -> String {
+        let mut out = String::new();
+        if self.staged {
+            out.push('S');
+        }
+        if self.modified {
+            out.push('M');
+        }
+        if self.untracked {
+            out.push('?');
+        }
+        if self.ignored {
+            out.push('!');
+        }
+        out
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.staged || self.modified || self.untracked
+    }
+}
+
+/// Run git in `dir` and return its stdout.
+pub fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let out = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .context("failed to run git")?;
+    if !out.status.success() {
+        bail!("git {}: {}", args.join(" "), String::from_utf8_lossy(&out.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).into_owned())
+}
+
+/// Top of the work tree containing `path`, if any.
+pub fn work_tree(path: &Path) -> Option<PathBuf> {
+    let dir = if path.is_dir() { path } else { path.parent()? };
+    let top = git(dir, &["rev-parse", "--show-toplevel"]).ok()?;
+    Some(PathBuf::from(top.trim_end()))
+}
+
+/// Status of every changed, untracked or ignored file below `top`, keyed by absolute path.
+fn statuses(top: &Path) -> HashMap<PathBuf, GitStatus> {
+    let mut out = HashMap::new();
+    let Ok(raw) = git(top, &["status", "--porcelain=v1", "-z", "--ignored", "--untracked-files=all"]) else {
+        return out;
+    };
+    let mut fields = raw.split('\0');
+    while let Some(rec) = fields.next() {
+        if rec.len() < 4 {
+            continue;
+        }
+        let (x, y) = (rec.as_bytes()[0], rec.as_bytes()[1]);
+        // Renames and copies carry the original path as an extra field
+        if matches!(x, b'R' | b'C') {
+            fields.next();
+        }
+        let status = match (x, y) {
+            (b'?', b'?') => GitStatus { untracked: true, ..Default::default() },
+            (b'!', b'!') => GitStatus { ignored: true, ..Default::default() },
+            _ => GitStatus { staged: x != b' ', modified: y != b' ', ..Default::default() },
+        };
+        out.insert(top.join(&rec[3..]), status);
+    }
+    out
+}
+
+/// Fill in `LiveEntry::git` for entries inside a work tree (following symlinks to their source).
+/// Runs `git status` once per work tree, and looks up the work tree once per directory.
+pub fn annotate(entries: &mut [LiveEntry]) {
+    let mut tops: HashMap<PathBuf, Option<PathBuf>> = HashMap::new();
+    let mut trees: HashMap<PathBuf, HashMap<PathBuf, GitStatus>> = HashMap::new();
+    for e in entries.iter_mut() {
+        let path = e.entry.edit_path().to_path_buf();
+        let Some(dir) = path.parent() else { continue };
+        let top = tops
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| work_tree(dir).map(|top| top.canonicalize().unwrap_or(top)));
+        e.git = top.as_ref().map(|top| {
+            let st = trees.entry(top.clone()).or_insert_with(|| statuses(top));
+            let canonical = path.canonicalize().unwrap_or(path);
+            // Ignored directories are reported once, not per file
+            st.get(&canonical)
+                .copied()
+                .or_else(|| canonical.ancestors().skip(1).find_map(|a| st.get(a).copied().filter(|s| s.ignored)))
+                .unwrap_or_default()
+        });
+    }
+}
+
+/// Staged and unstaged changes of one file against HEAD, as a colored unified diff.
+pub fn diff(entry: &ConfigEntry, status: GitStatus) -> String {
+    let path = entry.edit_path();
+    if status.untracked {
+        return "untracked file".to_string();
+    }
+    if !status.is_dirty() {
+        return "no changes".to_string();
+    }
+    let Some(dir) = path.parent() else { return String::new() };
+    let file = path.to_string_lossy();
+    // Fall back to the index for repositories without commits yet
+    let raw = git(dir, &["diff", "--color=always", "HEAD", "--", &file])
+        .or_else(|_| git(dir, &["diff", "--color=always", "--cached", "--", &file]))
+        .unwrap_or_else(|e| format!("{e:#}"));
+    if raw.is_empty() { "no changes".to_string() } else { raw }
+}
+
+fn prompt(question: &str) -> Result<String> {
+    print!("{question}");
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(answer.trim().to_string())
+}
+
+fn entry_dir(entry: &ConfigEntry) -> Result<(&Path, String)> {
+    let path = entry.edit_path();
+    let dir = path.parent().context("file has no parent directory")?;
+    Ok((dir, path.to_string_lossy().into_owned()))
+}
+
+pub fn stage(entry: &ConfigEntry) -> Result<()> {
+    let (dir, file) = entry_dir(entry)?;
+    git(dir, &["add", "--", &file])?;
+    println!("staged {file}");
+    Ok(())
+}
+
+/// Commit the file's changes (staging it first) with a message read from the terminal.
+pub fn commit(entry: &ConfigEntry) -> Result<()> {
+    let (dir, file) = entry_dir(entry)?;
+    let message = prompt(&format!("commit message for {file}: "))?;
+    if message.is_empty() {
+        println!("aborted: empty commit message");
+        return Ok(());
+    }
+    git(dir, &["add", "--", &file])?;
+    let out = git(dir, &["commit", "-m", &message, "--", &file])?;
+    print!("{out}");
+    Ok(())
+}
+
+/// Throw away staged and unstaged changes to a tracked file after confirmation. A file added
+/// since HEAD has nothing to restore to, so it is only unstaged and keeps its contents.
+pub fn discard(entry: &ConfigEntry, status: Option<GitStatus>) -> Result<()> {
+    let (dir, file) = entry_dir(entry)?;
+    if status.is_some_and(|s| s.untracked) {
+        bail!("{file} is untracked; nothing to restore it to");
+    }
+    let in_head = git(dir, &["ls-tree", "--name-only", "HEAD", "--", &file]).is_ok_and(|out| !out.is_empty());
+    let question = if in_head {
+        format!("discard all changes to {file}? [y/N] ")
+    } else {
+        format!("{file} is not in HEAD; unstage it (keeping its contents)? [y/N] ")
+    };
+    if !matches!(prompt(&question)?.as_str(), "y" | "Y" | "yes") {
+        println!("aborted");
+        return Ok(());
+    }
+    if in_head {
+        git(dir, &["restore", "--staged", "--worktree", "--source=HEAD", "--", &file])?;
+        println!("restored {file}");
+    } else {
+        git(dir, &["rm", "--cached", "--quiet", "--", &file])?;
+        println!("unstaged {file}");
+    }
+    Ok(())
+}
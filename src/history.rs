@@ -0,0 +1,244 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use hyprconf::xdg;
+
+use crate::{model::ConfigEntry, scan};
+
+/// Visits kept when the history file is compacted.
+const MAX_VISITS: usize = 2000;
+
+/// Order of the file picker when the query is empty.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortMode {
+    /// Often and recently opened files first
+    #[default]
+    Frecency,
+    /// The order Hyprland sources files in; unsourced files last
+    LoadOrder,
+    Alphabetical,
+    /// Most recently modified first
+    Modified,
+}
+
+impl SortMode {
+    pub fn next(self) -> Self {
+        match self {
+            SortMode::Frecency => SortMode::LoadOrder,
+            SortMode::LoadOrder => SortMode::Alphabetical,
+            SortMode::Alphabetical => SortMode::Modified,
+            SortMode::Modified => SortMode::Frecency,
+        }
+    }
+}
+
+impl fmt::Display for SortMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SortMode::Frecency => write!(f, "frecency"),
+            SortMode::LoadOrder => write!(f, "load order"),
+            SortMode::Alphabetical => write!(f, "alphabetical"),
+            SortMode::Modified => write!(f, "last modified"),
+        }
+    }
+}
+
+/// One open of a file, as logged in `$XDG_STATE_HOME/hyprconf/history`.
+#[derive(Debug, Clone)]
+pub struct Visit {
+    /// Seconds since the Unix epoch
+    pub time: u64,
+    pub path: PathBuf,
+    /// Device and inode at the time, to follow files renamed since
+    pub inode: Option<(u64, u64)>,
+    /// Line the editor was opened at
+    pub line: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    /// Oldest first
+    pub visits: Vec<Visit>,
+}
+
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+#[cfg(unix)]
+fn inode(path: &Path) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    fs::metadata(path).ok().map(|m| (m.dev(), m.ino()))
+}
+
+#[cfg(not(unix))]
+fn inode(_path: &Path) -> Option<(u64, u64)> {
+    None
+}
+
+fn parse_visit(line: &str) -> Option<Visit> {
+    // time \t line \t dev:ino \t path  (path last so it may contain anything but newlines)
+    let mut cols = line.splitn(4, '\t');
+    let time = cols.next()?.parse().ok()?;
+    let line = cols.next()?.parse().ok();
+    let inode = cols.next()?.split_once(':').and_then(|(d, i)| Some((d.parse().ok()?, i.parse().ok()?)));
+    let path = PathBuf::from(cols.next()?);
+    Some(Visit { time, path, inode, line })
+}
+
+fn format_visit(v: &Visit) -> String {
+    let line = v.line.map(|l| l.to_string()).unwrap_or_default();
+    let inode = v.inode.map(|(d, i)| format!("{d}:{i}")).unwrap_or_default();
+    format!("{}\t{line}\t{inode}\t{}\n", v.time, v.path.display())
+}
+
+/// Weight of one visit by age, in the style of Firefox's frecency buckets.
+fn recency_weight(age_secs: u64) -> f64 {
+    const HOUR: u64 = 3600;
+    const DAY: u64 = 24 * HOUR;
+    match age_secs {
+        a if a < 4 * HOUR => 100.0,
+        a if a < DAY => 80.0,
+        a if a < 7 * DAY => 60.0,
+        a if a < 30 * DAY => 40.0,
+        a if a < 90 * DAY => 20.0,
+        _ => 10.0,
+    }
+}
+
+impl History {
+    pub fn path() -> Result<PathBuf> {
+        Ok(xdg::dir("XDG_STATE_HOME", ".local/state")?.join("hyprconf").join("history"))
+    }
+
+    /// Load the history; a missing or unreadable file is an empty history.
+    pub fn load() -> Self {
+        let Ok(path) = Self::path() else { return Self::default() };
+        let visits = fs::read_to_string(path)
+            .map(|src| src.lines().filter_map(parse_visit).collect())
+            .unwrap_or_default();
+        Self { visits }
+    }
+
+    /// Append an open of `path` to the history file, compacting it when it grows too long.
+    pub fn record(path: &Path, line: Option<usize>) -> Result<()> {
+        let file = Self::path()?;
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let visit = Visit { time: now(), inode: inode(&path), path, line };
+
+        let mut history = Self::load();
+        if history.visits.len() >= MAX_VISITS {
+            history.visits.drain(..history.visits.len() + 1 - MAX_VISITS / 2);
+            history.visits.push(visit);
+            let out: String = history.visits.iter().map(format_visit).collect();
+            return fs::write(&file, out).with_context(|| format!("writing {}", file.display()));
+        }
+        let mut f = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&file)
+            .with_context(|| format!("opening {}", file.display()))?;
+        f.write_all(format_visit(&visit).as_bytes())?;
+        Ok(())
+    }
+
+    /// Visits of each entry, keyed by entry index. A visit belongs to the entry at its path or,
+    /// when that path no longer exists, to the entry with the same inode (a renamed file).
+    /// Visits of deleted files match nothing.
+    pub fn visits_by_entry<'a>(&'a self, entries: &[ConfigEntry]) -> HashMap<usize, Vec<&'a Visit>> {
+        let canonical: Vec<PathBuf> =
+            entries.iter().map(|e| e.path.canonicalize().unwrap_or_else(|_| e.path.clone())).collect();
+        let by_path: HashMap<&Path, usize> = canonical.iter().enumerate().map(|(i, p)| (p.as_path(), i)).collect();
+        let by_inode: HashMap<(u64, u64), usize> =
+            canonical.iter().enumerate().filter_map(|(i, p)| inode(p).map(|n| (n, i))).collect();
+
+        let mut out: HashMap<usize, Vec<&Visit>> = HashMap::new();
+        for v in &self.visits {
+            let i = by_path.get(v.path.as_path()).copied().or_else(|| {
+                if v.path.exists() { None } else { v.inode.and_then(|n| by_inode.get(&n).copied()) }
+            });
+            if let Some(i) = i {
+                out.entry(i).or_default().push(v);
+            }
+        }
+        out
+    }
+}
+
+/// An entry with its latest visit.
+#[derive(Debug, Clone)]
+pub struct Recent<'a> {
+    pub entry: &'a ConfigEntry,
+    pub time: u64,
+    /// Latest line the entry was opened at, if it ever was
+    pub line: Option<usize>,
+}
+
+impl History {
+    /// The `limit` most recently opened of `entries`, newest first.
+    pub fn recent<'a>(&self, entries: &'a [ConfigEntry], limit: usize) -> Vec<Recent<'a>> {
+        let mut out: Vec<Recent> = self
+            .visits_by_entry(entries)
+            .into_iter()
+            .filter_map(|(i, visits)| {
+                let time = visits.iter().map(|v| v.time).max()?;
+                let line = visits.iter().rev().find_map(|v| v.line);
+                Some(Recent { entry: &entries[i], time, line })
+            })
+            .collect();
+        out.sort_by_key(|r| std::cmp::Reverse(r.time));
+        out.truncate(limit);
+        out
+    }
+}
+
+/// Age of a timestamp for display, e.g. `5m ago`.
+pub fn ago(time: u64) -> String {
+    match now().saturating_sub(time) {
+        s if s < 60 => "just now".to_string(),
+        s if s < 3600 => format!("{}m ago", s / 60),
+        s if s < 86400 => format!("{}h ago", s / 3600),
+        s => format!("{}d ago", s / 86400),
+    }
+}
+
+fn modified(e: &ConfigEntry) -> SystemTime {
+    fs::metadata(&e.path).and_then(|m| m.modified()).unwrap_or(UNIX_EPOCH)
+}
+
+/// Reorder `entries` (already in `sort_key` order) for the picker.
+pub fn sort(entries: &mut Vec<ConfigEntry>, mode: SortMode, history: &History, root: &Path) {
+    match mode {
+        SortMode::Frecency => {
+            let now = now();
+            let visits = history.visits_by_entry(entries);
+            let score = |i: usize| -> f64 {
+                visits.get(&i).map_or(0.0, |vs| vs.iter().map(|v| recency_weight(now.saturating_sub(v.time))).sum())
+            };
+            let mut ranked: Vec<(f64, ConfigEntry)> = entries.drain(..).enumerate().map(|(i, e)| (score(i), e)).collect();
+            // Stable: unvisited entries keep their static order
+            ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+            entries.extend(ranked.into_iter().map(|(_, e)| e));
+        }
+        SortMode::LoadOrder => {
+            let order = scan::load_order(root);
+            let rank = |e: &ConfigEntry| {
+                let p = e.path.canonicalize().unwrap_or_else(|_| e.path.clone());
+                order.iter().position(|o| *o == p).unwrap_or(usize::MAX)
+            };
+            entries.sort_by_cached_key(rank);
+        }
+        SortMode::Alphabetical => entries.sort_by_cached_key(|e| e.alias.to_lowercase()),
+        SortMode::Modified => entries.sort_by_cached_key(|e| std::cmp::Reverse(modified(e))),
+    }
+}
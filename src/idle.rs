@@ -0,0 +1,172 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, bail};
+
+use crate::{
+    lint::{self, Diagnostic, Severity},
+    model::ConfigEntry,
+    parse::{self, LineKind},
+};
+
+/// One `listener { ... }` block.
+#[derive(Debug, Clone, Default)]
+pub struct Listener {
+    /// Line of the `listener {` opener
+    pub line: usize,
+    /// Seconds of inactivity
+    pub timeout: Option<u64>,
+    pub on_timeout: Option<String>,
+    pub on_resume: Option<String>,
+}
+
+impl Listener {
+    /// Short label for the timeline, guessed from the on-timeout command.
+    pub fn label(&self) -> String {
+        let Some(cmd) = self.on_timeout.as_deref() else { return "(no action)".to_string() };
+        let known = [
+            ("dpms off", "dpms off"),
+            ("brightnessctl", "dim"),
+            ("lock-session", "lock"),
+            ("hyprlock", "lock"),
+            ("suspend-then-hibernate", "suspend"),
+            ("hibernate", "hibernate"),
+            ("suspend", "suspend"),
+        ];
+        if let Some((_, label)) = known.iter().find(|(pat, _)| cmd.contains(pat)) {
+            return label.to_string();
+        }
+        lint::command_binaries(cmd)
+            .first()
+            .and_then(|b| Path::new(b).file_name().map(|n| n.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| cmd.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IdleConfig {
+    pub path: PathBuf,
+    /// `general { ... }` key/value pairs with their lines
+    pub general: Vec<(String, String, usize)>,
+    pub listeners: Vec<Listener>,
+}
+
+/// `150` -> "2:30", `3600` -> "1:00:00"
+pub fn format_duration(secs: u64) -> String {
+    let (h, m, s) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    if h > 0 { format!("{h}:{m:02}:{s:02}") } else { format!("{m}:{s:02}") }
+}
+
+pub fn parse_idle(path: &Path) -> Result<IdleConfig> {
+    let mut cfg = IdleConfig { path: path.to_path_buf(), ..Default::default() };
+    for line in parse::parse_file(path)? {
+        match (&line.kind, line.section.as_str()) {
+            (LineKind::Open { name, .. }, "") if name == "listener" => {
+                cfg.listeners.push(Listener { line: line.number, ..Default::default() });
+            }
+            (LineKind::Assign { key, value, .. }, "general") => {
+                cfg.general.push((key.clone(), value.clone(), line.number));
+            }
+            (LineKind::Assign { key, value, .. }, "listener") => {
+                let Some(l) = cfg.listeners.last_mut() else { continue };
+                match key.as_str() {
+                    "timeout" => l.timeout = value.parse().ok(),
+                    "on-timeout" => l.on_timeout = Some(value.clone()),
+                    "on-resume" => l.on_resume = Some(value.clone()),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    // Timeline order; listeners without a timeout sort last
+    cfg.listeners.sort_by_key(|l| (l.timeout.is_none(), l.timeout));
+    Ok(cfg)
+}
+
+pub fn check(cfg: &IdleConfig) -> Vec<Diagnostic> {
+    let mut out = Vec::new();
+    let mut diag = |line, severity, message| out.push(Diagnostic { path: cfg.path.clone(), line, severity, message });
+
+    for (i, l) in cfg.listeners.iter().enumerate() {
+        match l.timeout {
+            None => diag(l.line, Severity::Error, "listener without a valid timeout".to_string()),
+            Some(t) => {
+                if let Some(prev) = cfg.listeners[..i].iter().find(|p| p.timeout == Some(t)) {
+                    diag(l.line, Severity::Warning, format!("duplicate timeout {t} (also line {})", prev.line));
+                }
+            }
+        }
+        // Actions that change hardware state should be undone when the user comes back
+        if let Some(cmd) = &l.on_timeout {
+            let needs_resume = cmd.contains("dpms") || cmd.contains("brightnessctl") || cmd.contains("light ");
+            if needs_resume && l.on_resume.is_none() {
+                diag(l.line, Severity::Warning, format!("{:?} has no on-resume to restore it", l.label()));
+            }
+        }
+        let mut missing: Vec<String> = [&l.on_timeout, &l.on_resume]
+            .into_iter()
+            .flatten()
+            .flat_map(|cmd| lint::missing_binaries(cmd))
+            .collect();
+        missing.dedup();
+        for bin in missing {
+            diag(l.line, Severity::Warning, format!("command not found on $PATH: {bin}"));
+        }
+    }
+    for (key, value, line) in &cfg.general {
+        if key.ends_with("_cmd") {
+            for bin in lint::missing_binaries(value) {
+                diag(*line, Severity::Warning, format!("{key}: command not found on $PATH: {bin}"));
+            }
+        }
+    }
+    out
+}
+
+pub fn run(root: &Path, entries: &[ConfigEntry]) -> Result<()> {
+    let path = entries
+        .iter()
+        .find(|e| e.file_name == "hypridle.conf")
+        .map(|e| e.path.clone())
+        .unwrap_or_else(|| root.join("hypridle.conf"));
+    if !path.exists() {
+        bail!("no hypridle.conf in {}", root.display());
+    }
+    let cfg = parse_idle(&path)?;
+
+    println!("hypridle ({})\n", path.display());
+    if !cfg.general.is_empty() {
+        println!("general:");
+        for (key, value, _) in &cfg.general {
+            println!("  {key} = {value}");
+        }
+        println!();
+    }
+
+    println!("listeners:");
+    for l in &cfg.listeners {
+        let at = l.timeout.map(format_duration).unwrap_or_else(|| "?".to_string());
+        println!("  {at:>8}  {:<10} {}", l.label(), l.on_timeout.as_deref().unwrap_or(""));
+        if let Some(resume) = &l.on_resume {
+            println!("  {:>8}  {:<10} {resume}", "", "resume");
+        }
+    }
+
+    let timeline: Vec<String> = cfg
+        .listeners
+        .iter()
+        .filter_map(|l| l.timeout.map(|t| format!("{} {}", format_duration(t), l.label())))
+        .collect();
+    if !timeline.is_empty() {
+        println!("\n{}", timeline.join(" → "));
+    }
+
+    let diags = check(&cfg);
+    if !diags.is_empty() {
+        println!();
+        for d in &diags {
+            println!("{d}");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use std::{
+    env,
+    io::{Read, Write},
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+
+/// Directory holding the running instance's sockets, if Hyprland is reachable.
+pub fn instance_dir() -> Option<PathBuf> {
+    let sig = env::var("HYPRLAND_INSTANCE_SIGNATURE").ok()?;
+    let runtime = env::var("XDG_RUNTIME_DIR").ok();
+    // Hyprland >= 0.40 uses $XDG_RUNTIME_DIR/hypr, older releases /tmp/hypr
+    [runtime.map(|r| PathBuf::from(r).join("hypr")), Some(PathBuf::from("/tmp/hypr"))]
+        .into_iter()
+        .flatten()
+        .map(|base| base.join(&sig))
+        .find(|dir| dir.is_dir())
+}
+
+/// Hyprland's request socket (what `hyprctl` talks to).
+pub fn hyprland_socket() -> Option<PathBuf> {
+    instance_dir().map(|d| d.join(".socket.sock")).filter(|p| p.exists())
+}
+
+/// Send one request and read the whole reply; the server closes the connection when done.
+pub fn request(socket: &Path, cmd: &str) -> Result<String> {
+    let mut stream =
+        UnixStream::connect(socket).with_context(|| format!("connecting to {}", socket.display()))?;
+    stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+    stream.write_all(cmd.as_bytes())?;
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply)?;
+    Ok(reply)
+}
+
+/// Live value of an option via `getoption`, e.g. "int: 5" -> "5".
+pub fn get_option(option: &str) -> Option<String> {
+    let socket = hyprland_socket()?;
+    let reply = request(&socket, &format!("getoption {option}")).ok()?;
+    if reply.starts_with("no such option") {
+        return None;
+    }
+    reply
+        .lines()
+        .find(|l| !l.starts_with("option ") && !l.starts_with("set:") && l.contains(": "))
+        .and_then(|l| l.split_once(": "))
+        .map(|(_, v)| v.trim().to_string())
+}
@@ -0,0 +1,31 @@
+//! Scanning and parsing of Hyprland config directories, as used by the `hyprconf` picker.
+//!
+//! [`scan::Scanner`] finds the files below a config root (`hyprland.conf`, the hypr* utility
+//! configs, `conf.d/`, `themes/`, `plugins/` and `scripts/`) and describes each as a
+//! [`model::ConfigEntry`]; [`parse`] splits a file into classified lines, and [`lint`] checks
+//! their options against a [`schema::Schema`].
+//!
+//! ```no_run
+//! use hyprconf::{model::Category, scan::Scanner};
+//!
+//! let entries = Scanner::new("/home/me/.config/hypr").max_depth(2).scan()?;
+//! for e in entries.iter().filter(|e| e.category == Category::ConfD) {
+//!     println!("{} {}", e.alias, e.path.display());
+//! }
+//! # Ok::<(), hyprconf::Error>(())
+//! ```
+//!
+//! With the `serde` feature the model, parse and schema types implement `Serialize` and `Deserialize`.
+
+pub mod cache;
+pub mod color;
+pub mod dotfiles;
+mod error;
+pub mod lint;
+pub mod model;
+pub mod parse;
+pub mod scan;
+pub mod schema;
+pub mod xdg;
+
+pub use error::{Error, Result};
@@ -0,0 +1,153 @@
+use std::{
+    collections::HashMap,
+    env, fmt,
+    path::PathBuf,
+};
+
+use crate::{
+    model::{Category, ConfigEntry},
+    parse::{self, LineKind},
+    schema::Schema,
+    xdg::expand_tilde,
+};
+
+/// Keywords that look like assignments but are not options.
+const KEYWORDS: &[&str] = &[
+    "exec", "exec-once", "exec-shutdown", "execr", "execr-once", "source", "monitor", "env", "envd",
+    "windowrule", "windowrulev2", "layerrule", "workspace", "animation", "bezier", "plugin", "submap",
+    "blurls", "unbind", "permission", "gesture",
+];
+
+pub fn is_keyword(key: &str) -> bool {
+    parse::is_bind_keyword(key) || KEYWORDS.contains(&key)
+}
+
+/// Shell builtins and keywords that never live on `$PATH`.
+const BUILTINS: &[&str] = &[
+    "cd", "echo", "exec", "true", "false", "test", "[", "printf", "export", "eval", "source", ".", "if",
+    "then", "else", "fi", "for", "do", "done", "while", "case", "esac", "!", "{", "}", "wait", "kill",
+];
+
+/// Locate an executable like `which` does.
+pub fn which(cmd: &str) -> Option<PathBuf> {
+    if cmd.contains('/') {
+        let p = expand_tilde(cmd).ok()?;
+        return p.is_file().then_some(p);
+    }
+    env::split_paths(&env::var_os("PATH")?).map(|dir| dir.join(cmd)).find(|p| p.is_file())
+}
+
+/// Programs a shell command line runs: the first word of each `&&`, `||`, `;` and `|` segment,
+/// skipping `VAR=value` prefixes and variables other than `$HOME`.
+pub fn command_binaries(cmd: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    for segment in cmd.split(['&', '|', ';']) {
+        let word = segment
+            .split_whitespace()
+            .find(|w| !w.contains('=') || w.starts_with('='))
+            .map(|w| w.trim_matches(|c| c == '(' || c == ')' || c == '"' || c == '\''));
+        if let Some(word) = word
+            && !word.is_empty()
+            && (!word.starts_with('$') || word.starts_with("$HOME/") || word.starts_with("${HOME}/"))
+            && !out.iter().any(|o| o == word)
+        {
+            out.push(word.to_string());
+        }
+    }
+    out
+}
+
+/// Programs in `cmd` that are neither builtins nor found on `$PATH`.
+pub fn missing_binaries(cmd: &str) -> Vec<String> {
+    command_binaries(cmd)
+        .into_iter()
+        .filter(|b| !BUILTINS.contains(&b.as_str()) && which(b).is_none())
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub path: PathBuf,
+    pub line: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sev = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}:{}: {sev}: {}", self.path.display(), self.line, self.message)
+    }
+}
+
+/// Entries that hold Hyprland options (utility configs have their own keys).
+fn hyprland_entries(entries: &[ConfigEntry]) -> impl Iterator<Item = &ConfigEntry> {
+    entries
+        .iter()
+        .filter(|e| matches!(e.category, Category::Hyprland | Category::ConfD | Category::Themes | Category::Plugins))
+}
+
+/// Validate every option assignment against the schema.
+pub fn check(entries: &[ConfigEntry], schema: &Schema) -> Vec<Diagnostic> {
+    let parsed: Vec<(&ConfigEntry, Vec<parse::Line>)> = hyprland_entries(entries)
+        .filter_map(|e| parse::parse_file(&e.path).ok().map(|lines| (e, lines)))
+        .collect();
+    let vars: HashMap<String, String> = parse::variables(parsed.iter().flat_map(|(_, l)| l.iter()));
+
+    let mut out = Vec::new();
+    for e in entries {
+        if let Some(link) = &e.link
+            && link.broken
+            && let Some(target) = &link.target
+        {
+            let message = format!("broken symlink to {}", target.display());
+            out.push(Diagnostic { path: e.path.clone(), line: 0, severity: Severity::Error, message });
+        }
+        for program in e.header.requires.iter().filter(|p| which(p).is_none()) {
+            let message = format!("requires {program}, which is not on $PATH");
+            out.push(Diagnostic { path: e.path.clone(), line: 0, severity: Severity::Warning, message });
+        }
+    }
+    for (entry, lines) in &parsed {
+        for line in lines {
+            let (Some(path), LineKind::Assign { key, value, .. }) = (line.option_path(), &line.kind) else {
+                continue;
+            };
+            if is_keyword(key) {
+                continue;
+            }
+            let mut diag = |severity, message| {
+                out.push(Diagnostic { path: entry.path.clone(), line: line.number, severity, message })
+            };
+            match schema.get(&path) {
+                Some(spec) => {
+                    if let Some(v) = &spec.removed {
+                        diag(Severity::Warning, format!("{path} was removed in Hyprland {v}: {}", spec.description));
+                        continue;
+                    }
+                    // Values still referencing unknown variables can't be checked
+                    let resolved = parse::resolve_vars(value, &vars);
+                    if !resolved.contains('$')
+                        && let Some(problem) = spec.check_value(&resolved)
+                    {
+                        diag(Severity::Error, format!("{path}: {problem}"));
+                    }
+                }
+                None if schema.knows_section(&line.section) => {
+                    diag(Severity::Warning, format!("unknown option {path}"));
+                }
+                None => {}
+            }
+        }
+    }
+    out
+}
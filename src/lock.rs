@@ -0,0 +1,187 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Result, bail};
+use hyprconf::xdg;
+
+use crate::{
+    cli::LockArgs,
+    edit,
+    lint::{self, Diagnostic, Severity},
+    model::ConfigEntry,
+    parse::{self, LineKind},
+    ui::Picker,
+};
+
+const WIDGETS: &[&str] = &["background", "input-field", "label", "image", "shape"];
+
+/// Keys every widget accepts.
+const COMMON_KEYS: &[&str] = &[
+    "monitor", "position", "halign", "valign", "zindex", "shadow_passes", "shadow_size", "shadow_color",
+    "shadow_boost",
+];
+
+fn widget_keys(kind: &str) -> &'static [&'static str] {
+    match kind {
+        "background" => &[
+            "path", "color", "blur_passes", "blur_size", "noise", "contrast", "brightness", "vibrancy",
+            "vibrancy_darkness", "reload_time", "reload_cmd", "crossfade_time",
+        ],
+        "input-field" => &[
+            "size", "outline_thickness", "dots_size", "dots_spacing", "dots_center", "dots_rounding",
+            "dots_text_format", "outer_color", "inner_color", "font_color", "font_family", "fade_on_empty",
+            "fade_timeout", "placeholder_text", "hide_input", "hide_input_base_color", "rounding", "check_color",
+            "fail_color", "fail_text", "fail_timeout", "capslock_color", "numlock_color", "bothlock_color",
+            "invert_numlock", "swap_font_color",
+        ],
+        "label" => &["text", "text_align", "color", "font_size", "font_family", "rotate", "onclick"],
+        "image" => &[
+            "path", "size", "rounding", "border_size", "border_color", "rotate", "reload_time", "reload_cmd",
+            "onclick",
+        ],
+        "shape" => &["size", "color", "rounding", "rotate", "border_size", "border_color", "xray", "onclick"],
+        _ => &[],
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Widget {
+    pub kind: String,
+    /// Line of the `kind {` opener
+    pub line: usize,
+    /// Key, value and line of every field
+    pub fields: Vec<(String, String, usize)>,
+}
+
+impl Widget {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields.iter().rev().find(|(k, _, _)| k == key).map(|(_, v, _)| v.as_str())
+    }
+
+    /// The field that best describes the widget in an outline.
+    pub fn summary(&self) -> String {
+        let s = match self.kind.as_str() {
+            "label" => self.get("text"),
+            "image" => self.get("path"),
+            "input-field" => self.get("placeholder_text"),
+            "shape" => self.get("size"),
+            "background" => self.get("path").or(self.get("color")),
+            _ => None,
+        }
+        .unwrap_or("");
+        if s.chars().count() > 48 { format!("{}…", s.chars().take(47).collect::<String>()) } else { s.to_string() }
+    }
+
+    /// "position (halign/valign)", e.g. "0, 80 (center/center)"
+    pub fn placement(&self) -> String {
+        format!(
+            "{} ({}/{})",
+            self.get("position").unwrap_or("0, 0"),
+            self.get("halign").unwrap_or("center"),
+            self.get("valign").unwrap_or("center"),
+        )
+    }
+}
+
+pub fn parse_lock(path: &Path) -> Result<Vec<Widget>> {
+    let mut widgets: Vec<Widget> = Vec::new();
+    for line in parse::parse_file(path)? {
+        match &line.kind {
+            LineKind::Open { name, .. } if line.section.is_empty() && WIDGETS.contains(&name.as_str()) => {
+                widgets.push(Widget { kind: name.clone(), line: line.number, fields: Vec::new() });
+            }
+            LineKind::Assign { key, value, .. } if WIDGETS.contains(&line.section.as_str()) => {
+                if let Some(w) = widgets.last_mut() {
+                    w.fields.push((key.clone(), value.clone(), line.number));
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(widgets)
+}
+
+/// `cmd[update:1000] date +%R` -> `date +%R`
+fn label_command(text: &str) -> Option<&str> {
+    let rest = text.trim().strip_prefix("cmd[")?;
+    Some(rest.split_once(']')?.1.trim())
+}
+
+pub fn check(path: &Path, widgets: &[Widget], vars: &HashMap<String, String>) -> Vec<Diagnostic> {
+    let mut out = Vec::new();
+    let mut diag = |line, severity, message| out.push(Diagnostic { path: path.to_path_buf(), line, severity, message });
+    for w in widgets {
+        for (key, value, line) in &w.fields {
+            if !COMMON_KEYS.contains(&key.as_str()) && !widget_keys(&w.kind).contains(&key.as_str()) {
+                diag(*line, Severity::Warning, format!("unknown {} key: {key}", w.kind));
+            }
+            // `screenshot` is a special background source, not a file; variables hyprlock gets
+            // from elsewhere can't be checked
+            let value = parse::resolve_vars(value, vars);
+            if key == "path" && value != "screenshot" && !value.is_empty() && !value.contains('$') {
+                let p = xdg::expand_tilde(&value).unwrap_or_else(|_| PathBuf::from(&value));
+                if !p.is_file() {
+                    diag(*line, Severity::Error, format!("{}: image file does not exist: {value}", w.kind));
+                }
+            }
+            if key == "text" && let Some(cmd) = label_command(&value) {
+                for bin in lint::missing_binaries(cmd) {
+                    diag(*line, Severity::Warning, format!("label command not found on $PATH: {bin}"));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Outline row; `!` marks widgets with problems.
+fn outline_line(w: &Widget, problems: &[&Diagnostic]) -> String {
+    let monitor = w.get("monitor").filter(|m| !m.is_empty()).unwrap_or("all");
+    let mark = if problems.is_empty() { ' ' } else { '!' };
+    format!("{mark} {:<12} {:<28} [{monitor}] {}  (line {})", w.kind, w.placement(), w.summary(), w.line)
+}
+
+pub fn run(args: &LockArgs, root: &Path, entries: &[ConfigEntry], picker: &Picker, editor: Option<&str>) -> Result<()> {
+    let path = entries
+        .iter()
+        .find(|e| e.file_name == "hyprlock.conf")
+        .map(|e| e.path.clone())
+        .unwrap_or_else(|| root.join("hyprlock.conf"));
+    if !path.exists() {
+        bail!("no hyprlock.conf in {}", root.display());
+    }
+    let widgets = parse_lock(&path)?;
+    let vars = parse::variables(&parse::parse_file(&path)?);
+    let diags = check(&path, &widgets, &vars);
+    // Each widget's problems, matched by the lines of its fields
+    let problems: Vec<Vec<&Diagnostic>> = widgets
+        .iter()
+        .map(|w| diags.iter().filter(|d| w.fields.iter().any(|(_, _, line)| *line == d.line)).collect())
+        .collect();
+    let lines: Vec<String> = widgets.iter().zip(&problems).map(|(w, p)| outline_line(w, p)).collect();
+
+    if args.list {
+        println!("hyprlock ({})\n", path.display());
+        for l in &lines {
+            println!(" {l}");
+        }
+        if !diags.is_empty() {
+            println!();
+            for d in &diags {
+                println!("{d}");
+            }
+        }
+        return Ok(());
+    }
+
+    let previews = problems
+        .iter()
+        .map(|p| (!p.is_empty()).then(|| p.iter().map(|d| d.to_string()).collect::<Vec<_>>().join("\n")))
+        .collect();
+    if let Some(i) = picker.pick_index_with(&lines, previews)? {
+        edit::open_path(editor, entries, &path, Some(widgets[i].line), root)?;
+    }
+    Ok(())
+}
@@ -1,29 +1,185 @@
+mod blame;
+mod bookmarks;
+mod bundle;
+mod check;
 mod cli;
+mod config;
+mod diff;
 mod edit;
-mod model;
-mod scan;
+mod explain;
+mod fmt;
+mod git;
+mod history;
+mod idle;
+mod ipc;
+mod lock;
+mod migrate;
+mod palette;
+mod profile;
+mod query;
+mod recent;
+mod tsv;
 mod ui;
+mod wallpaper;
+mod watch;
 
-use anyhow::Result;
-use cli::Cli;
+use std::{io, path::Path};
+
+use anyhow::{Result, bail};
+use cli::{CacheCommand, Cli, Command};
 use clap::Parser;
-use scan::scan_configs;
-use ui::Picker;
+use config::Settings;
+// The scanning and parsing modules live in the library; importing them here keeps
+// `crate::model` and friends working in the binary's own modules
+use hyprconf::{cache, dotfiles, lint, model, parse, scan, schema};
+use history::{History, SortMode};
+use query::Filter;
+use scan::{Scanner, scan_lines};
+use schema::Schema;
+use ui::{EntryAction, LineAction, Picker};
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
-    // Resolve root directory
+    // Commands that don't look at the config root
+    if let Some(Command::Cache(args)) = &cli.command {
+        match args.command {
+            CacheCommand::Clear => {
+                let removed = cache::clear()?;
+                println!("removed {removed} cache file(s) from {}", cache::dir()?.display());
+                return Ok(());
+            }
+        }
+    }
+
+    // Resolve root directory and hyprconf's own settings
     let root = cli.resolve_root()?;
+    let settings = Settings::load(cli.config.as_deref())?;
 
-    // Collect entries
-    let mut entries = scan_configs(&root)?;
+    // Scan options: settings file, then flags
+    let mut scan_opts = settings.scan.clone();
+    if let Some(depth) = cli.max_depth {
+        scan_opts.max_depth = depth;
+    }
+    scan_opts.hidden |= cli.hidden;
+    scan_opts.cache &= !cli.no_cache;
+    // Diff scans its own two roots; import only reads the bundle
+    match &cli.command {
+        Some(Command::Diff(args)) => return diff::run(args, &scan_opts),
+        Some(Command::Import(args)) => return bundle::import(args, &root),
+        _ => {}
+    }
+    let scanner = Scanner::new(&root).options(scan_opts);
 
     // Build and run the picker
-    let picker = Picker::new(cli.category, cli.color_spec.clone(), !cli.no_seg_colors);
-    if let Some(selected) = picker.pick(&mut entries)? {
-        // Launch editor
-        edit::open_in_editor(cli.editor.as_deref(), &selected.path, &root)?;
+    let filter = Filter { category: cli.category, tag: cli.tag.clone() };
+    let active = profile::active(&root, &settings.profiles).cloned();
+    let picker = Picker::new(filter, active, cli.color_spec.clone(), !cli.no_seg_colors);
+
+    // Subcommands and line search work on the complete list
+    if let Some(command) = &cli.command {
+        let entries = scanner.scan()?;
+        return match command {
+            Command::Palette(args) => palette::run(args, &entries, &picker),
+            Command::Fmt(args) => fmt::run(args, &entries, &settings.fmt),
+            Command::Check => {
+                let schema = Schema::from_settings(settings.schema_path.as_deref())?;
+                check::run(&entries, &schema)
+            }
+            Command::Explain(args) => {
+                let schema = Schema::from_settings(settings.schema_path.as_deref())?;
+                explain::run(args, &root, &entries, &schema)
+            }
+            Command::Migrate(args) => migrate::run(args, &entries),
+            Command::Export(args) => bundle::export(args, &root, &entries),
+            Command::Idle => idle::run(&root, &entries),
+            Command::Wallpaper(args) => wallpaper::run(args, &root, &entries, &picker),
+            Command::Lock(args) => lock::run(args, &root, &entries, &picker, cli.editor.as_deref()),
+            Command::Bookmarks(args) => bookmarks::run(args, &root, &entries, &picker, cli.editor.as_deref()),
+            Command::Recent(args) => recent::run(args, &root, &entries, &picker, cli.editor.as_deref()),
+            Command::Profile(args) => profile::run(args, &root, &entries, &settings.profiles),
+            Command::Cache(_) | Command::Diff(_) | Command::Import(_) => {
+                unreachable!("handled before scanning")
+            }
+        };
+    }
+
+    if cli.last {
+        let entries = scanner.scan()?;
+        let Some(last) = History::load().recent(&entries, 1).pop() else {
+            bail!("no file under {} has been opened with hyprconf yet", root.display());
+        };
+        return edit::open_entry(cli.editor.as_deref(), last.entry, last.line, &root);
+    }
+
+    if cli.lines {
+        let entries = scanner.scan()?;
+        let schema = Schema::from_settings(settings.schema_path.as_deref())?;
+        return line_search(&cli, &picker, &root, &entries, &schema);
+    }
+
+    // The file picker scans on its own, streaming entries in as they are read
+    let mut query: Option<String> = None;
+    let mut sort = SortMode::default();
+    let mut category = cli.category;
+    while let Some(pick) = picker.pick(&scanner, sort, category, query.as_deref(), cli.watch)? {
+        let result = match pick.action {
+            EntryAction::Open => return edit::open_entry(cli.editor.as_deref(), &pick.entry, None, &root),
+            EntryAction::Pin => bookmarks::toggle_pin(&pick.entry),
+            EntryAction::Sort => {
+                sort = sort.next();
+                Ok(())
+            }
+            EntryAction::Stage => git::stage(&pick.entry),
+            EntryAction::Commit => git::commit(&pick.entry),
+            EntryAction::Discard => git::discard(&pick.entry, pick.git),
+        };
+        // Report and return to the picker with the same query
+        if let Err(e) = result {
+            eprintln!("error: {e:#}");
+        }
+        query = Some(pick.query);
+        category = pick.category;
+    }
+    Ok(())
+}
+
+fn line_search(cli: &Cli, picker: &Picker, root: &Path, entries: &[model::ConfigEntry], schema: &Schema) -> Result<()> {
+    let lines = scan_lines(entries);
+    let mut query: Option<String> = None;
+    while let Some(pick) = picker.pick_line(&lines, schema, query.as_deref())? {
+        match pick.action {
+            LineAction::Open => {
+                return edit::open_path(cli.editor.as_deref(), entries, &pick.line.path, Some(pick.line.line), root);
+            }
+            LineAction::Explain => {
+                match &pick.line.option {
+                    Some(option) => print!("{}", explain::explain(root, entries, schema, option).to_text(root)),
+                    None => println!("{}:{} does not set an option", pick.line.path.display(), pick.line.line),
+                }
+                println!("\n[press Enter to return]");
+                io::stdin().read_line(&mut String::new())?;
+                query = Some(pick.query);
+            }
+            LineAction::Blame => {
+                match blame::blame(&pick.line) {
+                    Ok(b) => print!("{}", b.to_text(root)),
+                    Err(e) => println!("{e:#}"),
+                }
+                println!("\n[press Enter to return]");
+                io::stdin().read_line(&mut String::new())?;
+                query = Some(pick.query);
+            }
+            LineAction::Bookmark => {
+                match bookmarks::toggle_bookmark(&pick.line) {
+                    Ok(msg) => println!("{msg}"),
+                    Err(e) => println!("{e:#}"),
+                }
+                println!("\n[press Enter to return]");
+                io::stdin().read_line(&mut String::new())?;
+                query = Some(pick.query);
+            }
+        }
     }
     Ok(())
 }
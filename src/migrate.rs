@@ -0,0 +1,425 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result};
+use similar::TextDiff;
+
+use crate::{
+    cli::MigrateArgs,
+    model::{Category, ConfigEntry},
+    parse::{self, LineKind},
+};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Transform {
+    /// Option renamed or moved to another section; `value` rewrites the value if the format changed
+    Rename { from: &'static str, to: &'static str, value: Option<fn(&str) -> String> },
+    /// Option removed without a replacement; the line is commented out
+    Remove { option: &'static str },
+    /// `windowrule = RULE, REGEX` -> `windowrulev2 = RULE, class:REGEX` (or `title:REGEX`)
+    WindowRuleV2,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Rule {
+    pub id: &'static str,
+    /// First Hyprland version that needs the rewrite
+    pub since: &'static str,
+    pub transform: Transform,
+    pub note: &'static str,
+}
+
+fn invert_bool(v: &str) -> String {
+    match v.trim() {
+        "true" | "yes" | "on" | "1" => "false".to_string(),
+        "false" | "no" | "off" | "0" => "true".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn master_status(v: &str) -> String {
+    match v.trim() {
+        "true" | "yes" | "on" | "1" => "master".to_string(),
+        _ => "slave".to_string(),
+    }
+}
+
+/// Known rewrites, oldest first.
+pub const RULES: &[Rule] = &[
+    Rule {
+        id: "cursor-inactive-timeout",
+        since: "0.37.0",
+        transform: Transform::Rename { from: "general:cursor_inactive_timeout", to: "cursor:inactive_timeout", value: None },
+        note: "cursor options moved to the cursor section",
+    },
+    Rule {
+        id: "cursor-no-warps",
+        since: "0.37.0",
+        transform: Transform::Rename { from: "general:no_cursor_warps", to: "cursor:no_warps", value: None },
+        note: "cursor options moved to the cursor section",
+    },
+    Rule {
+        id: "general-sensitivity",
+        since: "0.38.0",
+        transform: Transform::Remove { option: "general:sensitivity" },
+        note: "removed; use input:sensitivity",
+    },
+    Rule {
+        id: "apply-sens-to-raw",
+        since: "0.38.0",
+        transform: Transform::Remove { option: "general:apply_sens_to_raw" },
+        note: "removed without a replacement",
+    },
+    Rule {
+        id: "master-new-is-master",
+        since: "0.41.0",
+        transform: Transform::Rename { from: "master:new_is_master", to: "master:new_status", value: Some(master_status) },
+        note: "replaced by master:new_status (master/slave)",
+    },
+    Rule {
+        id: "direct-scanout",
+        since: "0.42.0",
+        transform: Transform::Rename { from: "misc:no_direct_scanout", to: "render:direct_scanout", value: Some(invert_bool) },
+        note: "moved to render:direct_scanout with inverted meaning",
+    },
+    Rule {
+        id: "no-border-on-floating",
+        since: "0.42.0",
+        transform: Transform::Remove { option: "general:no_border_on_floating" },
+        note: "removed; use a `noborder` window rule for floating windows",
+    },
+    Rule {
+        id: "shadow-enabled",
+        since: "0.45.0",
+        transform: Transform::Rename { from: "decoration:drop_shadow", to: "decoration:shadow:enabled", value: None },
+        note: "shadow options moved to decoration:shadow",
+    },
+    Rule {
+        id: "shadow-range",
+        since: "0.45.0",
+        transform: Transform::Rename { from: "decoration:shadow_range", to: "decoration:shadow:range", value: None },
+        note: "shadow options moved to decoration:shadow",
+    },
+    Rule {
+        id: "shadow-render-power",
+        since: "0.45.0",
+        transform: Transform::Rename { from: "decoration:shadow_render_power", to: "decoration:shadow:render_power", value: None },
+        note: "shadow options moved to decoration:shadow",
+    },
+    Rule {
+        id: "shadow-ignore-window",
+        since: "0.45.0",
+        transform: Transform::Rename { from: "decoration:shadow_ignore_window", to: "decoration:shadow:ignore_window", value: None },
+        note: "shadow options moved to decoration:shadow",
+    },
+    Rule {
+        id: "shadow-offset",
+        since: "0.45.0",
+        transform: Transform::Rename { from: "decoration:shadow_offset", to: "decoration:shadow:offset", value: None },
+        note: "shadow options moved to decoration:shadow",
+    },
+    Rule {
+        id: "shadow-scale",
+        since: "0.45.0",
+        transform: Transform::Rename { from: "decoration:shadow_scale", to: "decoration:shadow:scale", value: None },
+        note: "shadow options moved to decoration:shadow",
+    },
+    Rule {
+        id: "shadow-color",
+        since: "0.45.0",
+        transform: Transform::Rename { from: "decoration:col.shadow", to: "decoration:shadow:color", value: None },
+        note: "shadow options moved to decoration:shadow",
+    },
+    Rule {
+        id: "shadow-color-inactive",
+        since: "0.45.0",
+        transform: Transform::Rename { from: "decoration:col.shadow_inactive", to: "decoration:shadow:color_inactive", value: None },
+        note: "shadow options moved to decoration:shadow",
+    },
+    Rule {
+        id: "dwindle-no-gaps-when-only",
+        since: "0.45.0",
+        transform: Transform::Remove { option: "dwindle:no_gaps_when_only" },
+        note: "removed; use workspace rules for smart gaps",
+    },
+    Rule {
+        id: "master-no-gaps-when-only",
+        since: "0.45.0",
+        transform: Transform::Remove { option: "master:no_gaps_when_only" },
+        note: "removed; use workspace rules for smart gaps",
+    },
+    Rule {
+        id: "windowrule-v2",
+        since: "0.46.0",
+        transform: Transform::WindowRuleV2,
+        note: "windowrule v1 syntax is deprecated; match the class explicitly",
+    },
+];
+
+fn version_key(v: &str) -> Vec<u32> {
+    let mut key: Vec<u32> = v.trim_start_matches('v').split('.').map(|p| p.parse().unwrap_or(0)).collect();
+    // "0.46" and "0.46.0" are the same release
+    while key.last() == Some(&0) {
+        key.pop();
+    }
+    key
+}
+
+/// One rule applied to one line.
+#[derive(Debug, Clone)]
+pub struct Applied {
+    pub path: PathBuf,
+    pub line: usize,
+    pub rule: &'static Rule,
+}
+
+/// Replace the key token of an assignment line, keeping indentation and everything after it.
+fn replace_key(raw: &str, key: &str, new_key: &str) -> String {
+    match raw.find(key) {
+        Some(pos) => format!("{}{}{}", &raw[..pos], new_key, &raw[pos + key.len()..]),
+        None => raw.to_string(),
+    }
+}
+
+/// Replace the value of an assignment line, keeping any trailing comment.
+fn replace_value(raw: &str, value: &str, new_value: &str) -> String {
+    let Some(eq) = raw.find('=') else { return raw.to_string() };
+    match raw[eq..].find(value) {
+        Some(off) if !value.is_empty() => {
+            let pos = eq + off;
+            format!("{}{}{}", &raw[..pos], new_value, &raw[pos + value.len()..])
+        }
+        _ => raw.to_string(),
+    }
+}
+
+/// `class:^(kitty)$`, `floating:1`: the regex already starts with a `<field>:` matcher.
+fn has_field(regex: &str) -> bool {
+    regex.split_once(':').is_some_and(|(field, _)| !field.is_empty() && field.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+fn indent_of(raw: &str) -> &str {
+    &raw[..raw.len() - raw.trim_start().len()]
+}
+
+/// Rewrite one file; returns the new contents and the rules applied.
+pub fn migrate_str(src: &str, rules: &[&'static Rule]) -> (String, Vec<(usize, &'static Rule)>) {
+    let mut out = String::with_capacity(src.len());
+    let mut appended: Vec<String> = Vec::new();
+    let mut applied = Vec::new();
+
+    // Each line keeps its own ending, so CRLF files stay CRLF
+    for (line, full) in parse::parse_str(src).iter().zip(src.split_inclusive('\n')) {
+        let raw = full.strip_suffix('\n').map(|t| t.strip_suffix('\r').unwrap_or(t)).unwrap_or(full);
+        let ending = &full[raw.len()..];
+        let LineKind::Assign { key, value, .. } = &line.kind else {
+            out.push_str(raw);
+            out.push_str(ending);
+            continue;
+        };
+        let path = line.option_path().unwrap_or_default();
+        let mut new_raw = raw.to_string();
+        for rule in rules {
+            match rule.transform {
+                Transform::Rename { from, to, value: rewrite } if path == from => {
+                    let new_value = rewrite.map(|f| f(value)).unwrap_or_else(|| value.clone());
+                    let prefix = if line.section.is_empty() { String::new() } else { format!("{}:", line.section) };
+                    match to.strip_prefix(&prefix) {
+                        // Target lives under the current block: rename in place (may nest, e.g. shadow:range)
+                        Some(rel) => {
+                            new_raw = replace_value(&replace_key(raw, key, rel), value, &new_value);
+                        }
+                        // Different top-level section: comment out here, set the full path at the end
+                        None => {
+                            new_raw = format!("{}# {}  # moved to {to}", indent_of(raw), raw.trim());
+                            appended.push(format!("{to} = {new_value}  # migrated from {from}"));
+                        }
+                    }
+                    applied.push((line.number, *rule));
+                }
+                Transform::Remove { option } if path == option => {
+                    // Notes of removals start with "removed"
+                    let note = if rule.note.is_empty() { "removed" } else { rule.note };
+                    new_raw = format!("{}# {}  # {note}", indent_of(raw), raw.trim());
+                    applied.push((line.number, *rule));
+                }
+                Transform::WindowRuleV2 if key == "windowrule" => {
+                    if let Some((rule_part, regex)) = value.rsplit_once(',') {
+                        // v1 matched the class unless the regex named a field (`title:`, or a v2
+                        // matcher such as `initialClass:` written with the old keyword)
+                        let regex = regex.trim();
+                        let matcher = if has_field(regex) { regex.to_string() } else { format!("class:{regex}") };
+                        let new_value = format!("{}, {matcher}", rule_part.trim());
+                        new_raw = replace_value(&replace_key(raw, key, "windowrulev2"), value, &new_value);
+                        applied.push((line.number, *rule));
+                    }
+                }
+                _ => {}
+            }
+        }
+        out.push_str(&new_raw);
+        out.push_str(ending);
+    }
+
+    if !appended.is_empty() {
+        let newline = if src.contains("\r\n") { "\r\n" } else { "\n" };
+        if !out.is_empty() && !out.ends_with('\n') {
+            out.push_str(newline);
+        }
+        out.push_str(newline);
+        out.push_str(&appended.join(newline));
+        if src.ends_with('\n') {
+            out.push_str(newline);
+        }
+    }
+    (out, applied)
+}
+
+/// Rules needed to reach Hyprland `to` (all of them when `None`).
+pub fn rules_up_to(to: Option<&str>) -> Vec<&'static Rule> {
+    let target = to.map(version_key);
+    RULES.iter().filter(|r| target.as_ref().is_none_or(|t| version_key(r.since) <= *t)).collect()
+}
+
+pub fn run(args: &MigrateArgs, entries: &[ConfigEntry]) -> Result<()> {
+    let rules = rules_up_to(args.to.as_deref());
+
+    let mut changes: Vec<(PathBuf, String)> = Vec::new();
+    let mut applied: Vec<Applied> = Vec::new();
+    for e in entries.iter().filter(|e| !matches!(e.category, Category::Utility | Category::Scripts)) {
+        let src = fs::read_to_string(&e.path).with_context(|| format!("reading {}", e.path.display()))?;
+        let (new, hits) = migrate_str(&src, &rules);
+        if hits.is_empty() {
+            continue;
+        }
+        let name = e.path.display().to_string();
+        print!("{}", TextDiff::from_lines(&src, &new).unified_diff().header(&name, &name));
+        applied.extend(hits.into_iter().map(|(line, rule)| Applied { path: e.path.clone(), line, rule }));
+        changes.push((e.path.clone(), new));
+    }
+
+    if changes.is_empty() {
+        println!("nothing to migrate");
+        return Ok(());
+    }
+
+    if !args.yes {
+        print!("\nApply {} change(s) to {} file(s)? [y/N] ", applied.len(), changes.len());
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            println!("aborted");
+            return Ok(());
+        }
+    }
+
+    for (path, new) in &changes {
+        fs::write(path, new).with_context(|| format!("writing {}", path.display()))?;
+    }
+    for a in &applied {
+        println!("{}:{}: [{}] {} (Hyprland {})", a.path.display(), a.line, a.rule.id, a.rule.note, a.rule.since);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn migrate(src: &str) -> (String, Vec<&'static str>) {
+        let (out, applied) = migrate_str(src, &rules_up_to(None));
+        (out, applied.into_iter().map(|(_, r)| r.id).collect())
+    }
+
+    #[test]
+    fn rename_within_the_block_keeps_indent_value_and_comment() {
+        let (out, ids) = migrate("decoration {\n    drop_shadow = true # soft\n    rounding = 8\n}\n");
+        assert_eq!(out, "decoration {\n    shadow:enabled = true # soft\n    rounding = 8\n}\n");
+        assert_eq!(ids, ["shadow-enabled"]);
+    }
+
+    #[test]
+    fn rename_across_sections_comments_out_and_appends_the_new_path() {
+        let (out, ids) = migrate("general {\n    no_cursor_warps = true\n    gaps_in = 5\n}\n");
+        assert_eq!(
+            out,
+            "general {\n    # no_cursor_warps = true  # moved to cursor:no_warps\n    gaps_in = 5\n}\n\n\
+             cursor:no_warps = true  # migrated from general:no_cursor_warps\n"
+        );
+        assert_eq!(ids, ["cursor-no-warps"]);
+    }
+
+    #[test]
+    fn rename_rewrites_the_value() {
+        let (out, _) = migrate("master {\n    new_is_master = true\n}\nmaster:new_is_master = no\n");
+        assert_eq!(out, "master {\n    new_status = master\n}\nmaster:new_status = slave\n");
+
+        let (out, ids) = migrate("misc {\n    no_direct_scanout = true\n}\n");
+        assert_eq!(
+            out,
+            "misc {\n    # no_direct_scanout = true  # moved to render:direct_scanout\n}\n\n\
+             render:direct_scanout = false  # migrated from misc:no_direct_scanout\n"
+        );
+        assert_eq!(ids, ["direct-scanout"]);
+    }
+
+    #[test]
+    fn remove_comments_the_line_out() {
+        let (out, ids) = migrate("general {\n    sensitivity = 1.0\n}\ndwindle:no_gaps_when_only = 1\n");
+        assert_eq!(
+            out,
+            "general {\n    # sensitivity = 1.0  # removed; use input:sensitivity\n}\n\
+             # dwindle:no_gaps_when_only = 1  # removed; use workspace rules for smart gaps\n"
+        );
+        assert_eq!(ids, ["general-sensitivity", "dwindle-no-gaps-when-only"]);
+    }
+
+    #[test]
+    fn windowrule_v2_matches_the_class_unless_a_field_is_named() {
+        let (out, ids) = migrate(
+            "windowrule = float, ^(pavucontrol)$\nwindowrule = opacity 0.9, title:^(Picture)$\n\
+             windowrulev2 = float, class:^(mpv)$\n",
+        );
+        assert_eq!(
+            out,
+            "windowrulev2 = float, class:^(pavucontrol)$\nwindowrulev2 = opacity 0.9, title:^(Picture)$\n\
+             windowrulev2 = float, class:^(mpv)$\n"
+        );
+        assert_eq!(ids, ["windowrule-v2", "windowrule-v2"]);
+    }
+
+    #[test]
+    fn line_endings_are_kept() {
+        let (out, _) = migrate("general {\r\n    no_cursor_warps = true\r\n}\r\nwindowrule = float, ^(mpv)$\r\n");
+        assert_eq!(
+            out,
+            "general {\r\n    # no_cursor_warps = true  # moved to cursor:no_warps\r\n}\r\n\
+             windowrulev2 = float, class:^(mpv)$\r\n\r\n\
+             cursor:no_warps = true  # migrated from general:no_cursor_warps\r\n"
+        );
+
+        let (out, _) = migrate("misc {\n    no_direct_scanout = false\n}");
+        assert_eq!(
+            out,
+            "misc {\n    # no_direct_scanout = false  # moved to render:direct_scanout\n}\n\n\
+             render:direct_scanout = true  # migrated from misc:no_direct_scanout"
+        );
+    }
+
+    #[test]
+    fn to_stops_at_the_target_version() {
+        let ids = |to| rules_up_to(to).into_iter().map(|r| r.id).collect::<Vec<_>>();
+        assert_eq!(ids(Some("0.37")), ["cursor-inactive-timeout", "cursor-no-warps"]);
+        assert_eq!(ids(Some("v0.41.0")).last(), Some(&"master-new-is-master"));
+        assert!(!ids(Some("0.45.9")).contains(&"windowrule-v2"));
+        assert_eq!(ids(None).len(), RULES.len());
+
+        let src = "decoration {\n    drop_shadow = true\n}\n";
+        let (out, applied) = migrate_str(src, &rules_up_to(Some("0.44.0")));
+        assert_eq!((out.as_str(), applied.len()), (src, 0));
+    }
+}
@@ -1,9 +1,16 @@
-use std::{fmt, path::PathBuf};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
-use clap::ValueEnum;
+use crate::Error;
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, ValueEnum)]
-#[value(rename_all = "kebab-case")]
+/// Kind of config file, from its name or the directory it was found in.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+#[non_exhaustive]
 pub enum Category {
     Hyprland,
     Utility,
@@ -26,17 +33,160 @@ impl fmt::Display for Category {
     }
 }
 
+impl Category {
+    /// Every category, in picker order.
+    pub const ALL: [Category; 6] =
+        [Category::Hyprland, Category::Utility, Category::Themes, Category::Plugins, Category::ConfD, Category::Scripts];
+
+    /// Name on the command line and in bundle manifests, e.g. "conf-d"; `Display` gives "conf.d".
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::Hyprland => "hyprland",
+            Category::Utility => "utility",
+            Category::Themes => "themes",
+            Category::Plugins => "plugins",
+            Category::ConfD => "conf-d",
+            Category::Scripts => "scripts",
+        }
+    }
+}
+
+impl FromStr for Category {
+    type Err = Error;
+
+    /// Parses [`Category::name`].
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Category::ALL.into_iter().find(|c| c.name() == s).ok_or_else(|| Error::UnknownCategory(s.to_string()))
+    }
+}
+
+/// One file found by a scan.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub struct ConfigEntry {
     pub path: PathBuf,
     pub file_name: String,
     pub alias: String,
     pub description: Option<String>,
     pub category: Category,
+    /// Set when the file is a symlink, read-only or deployed by a dotfile manager
+    pub link: Option<Link>,
+    /// `# @key: value` metadata from the top of the file
+    pub header: Header,
+    /// What the file contains, counted when it was scanned
+    pub summary: Summary,
+}
+
+/// Optional header block at the top of a file, e.g. `# @alias: binds`, `# @tags: input, keyboard`.
+/// `alias` and `description` override the derived ones.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Header {
+    pub alias: Option<String>,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    /// Machines the file is meant for
+    pub hosts: Vec<String>,
+    /// Programs the file needs
+    pub requires: Vec<String>,
+}
+
+/// Line counts of a scanned file; scripts only get `lines`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Summary {
+    pub lines: usize,
+    /// Options and keywords such as `exec-once` or `windowrule`, but not the counts below
+    pub assignments: usize,
+    pub binds: usize,
+    pub variables: usize,
+    pub sources: usize,
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = |n: usize, what: &str| format!("{n} {what}{}", if n == 1 { "" } else { "s" });
+        write!(f, "{}", plural(self.lines, "line"))?;
+        let counts = [
+            (self.assignments, "assignment"),
+            (self.binds, "bind"),
+            (self.variables, "variable"),
+            (self.sources, "source"),
+        ];
+        let parts: Vec<String> = counts.iter().filter(|(n, _)| *n > 0).map(|(n, what)| plural(*n, what)).collect();
+        if !parts.is_empty() {
+            write!(f, ": {}", parts.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Dotfile managers whose deployed files we can trace back to a source.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[non_exhaustive]
+pub enum Manager {
+    Stow,
+    Chezmoi,
+    Yadm,
+    /// home-manager and other Nix-generated files in `/nix/store`
+    Nix,
+}
+
+impl fmt::Display for Manager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Manager::Stow => write!(f, "stow"),
+            Manager::Chezmoi => write!(f, "chezmoi"),
+            Manager::Yadm => write!(f, "yadm"),
+            Manager::Nix => write!(f, "nix"),
+        }
+    }
+}
+
+/// How a file got where it is, when it is not a plain file of the user's.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Link {
+    /// Symlink target (resolved when possible); None for regular files
+    pub target: Option<PathBuf>,
+    /// Symlink whose target doesn't exist
+    pub broken: bool,
+    pub read_only: bool,
+    pub manager: Option<Manager>,
+    /// Source-of-truth file to edit instead of the deployed copy
+    pub source: Option<PathBuf>,
+}
+
+/// One line of a scanned file, for line-search mode.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConfigLine {
+    pub path: PathBuf,
+    pub line: usize,
+    pub text: String,
+    /// Full option path when the line assigns a Hyprland option
+    pub option: Option<String>,
 }
 
 impl ConfigEntry {
-    pub fn sort_key(&self) -> (u8, String) {
+    /// File to open in the editor: the manager's source, else the symlink target, else the path.
+    pub fn edit_path(&self) -> &Path {
+        match &self.link {
+            Some(Link { source: Some(src), .. }) => src,
+            Some(Link { target: Some(t), broken: false, .. }) => t,
+            _ => &self.path,
+        }
+    }
+
+    /// Category order, then a per-category name; the path breaks ties (header aliases can
+    /// repeat), so the order never depends on the order the scan found the entries in.
+    pub fn sort_key(&self) -> (u8, String, PathBuf) {
         let cat_order = match self.category {
             Category::Hyprland => 0,
             Category::Utility => 1,
@@ -48,11 +198,12 @@ impl ConfigEntry {
         let within = match self.category {
             // Keep utilities readable by alias; hyprland is single file anyway
             Category::Hyprland | Category::Utility => self.alias.to_lowercase(),
-            // Sort conf.d entries by filename alphabetically (e.g., 00-..., 05-..., ...)
-            Category::ConfD => self.file_name.to_lowercase(),
+            // Sort conf.d entries by path below conf.d (e.g., 00-..., 05-..., laptop/10-...), so
+            // each subdirectory stays together; the paths share the conf.d prefix
+            Category::ConfD => self.path.to_string_lossy().to_lowercase(),
             // Themes, plugins and scripts by alias
             Category::Themes | Category::Plugins | Category::Scripts => self.alias.to_lowercase(),
         };
-        (cat_order, within)
+        (cat_order, within, self.path.clone())
     }
 }
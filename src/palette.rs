@@ -0,0 +1,180 @@
+use std::{collections::HashMap, path::{Path, PathBuf}};
+
+use anyhow::{Result, bail};
+use clap::ValueEnum;
+use hyprconf::color::{ColorValue, Rgba, parse_color_value};
+
+use crate::{
+    cli::PaletteArgs,
+    model::{Category, ConfigEntry},
+    parse::{self, LineKind},
+    ui::Picker,
+};
+
+/// Color notations Hyprland accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum Notation {
+    /// rgba(RRGGBBAA)
+    Rgba,
+    /// rgba(r, g, b, a) with alpha in 0..1
+    RgbaDecimal,
+    /// rgb(RRGGBB)
+    Rgb,
+    /// rgb(r, g, b)
+    RgbDecimal,
+    /// 0xAARRGGBB (legacy)
+    Hex,
+}
+
+#[derive(Debug, Clone)]
+pub struct PaletteEntry {
+    /// Option path (`general:col.active_border`) or `$variable`
+    pub name: String,
+    pub path: PathBuf,
+    pub line: usize,
+    pub raw: String,
+    pub value: ColorValue,
+}
+
+/// One color in `notation`.
+pub fn format_color(c: Rgba, notation: Notation) -> String {
+    let Rgba { r, g, b, a } = c;
+    match notation {
+        Notation::Rgba => format!("rgba({r:02x}{g:02x}{b:02x}{a:02x})"),
+        Notation::RgbaDecimal => {
+            let alpha = format!("{:.3}", a as f32 / 255.0);
+            let alpha = alpha.trim_end_matches('0').trim_end_matches('.');
+            format!("rgba({r}, {g}, {b}, {})", if alpha.is_empty() { "0" } else { alpha })
+        }
+        Notation::Rgb => format!("rgb({r:02x}{g:02x}{b:02x})"),
+        Notation::RgbDecimal => format!("rgb({r}, {g}, {b})"),
+        Notation::Hex => format!("0x{a:02x}{r:02x}{g:02x}{b:02x}"),
+    }
+}
+
+/// A color or gradient with every stop in `notation`.
+pub fn format_value(value: &ColorValue, notation: Notation) -> String {
+    let mut parts: Vec<String> = value.stops.iter().map(|c| format_color(*c, notation)).collect();
+    if let Some(angle) = value.angle {
+        parts.push(format!("{angle}deg"));
+    }
+    parts.join(" ")
+}
+
+/// The value in every notation, one per line, for printing after selection.
+pub fn conversions(value: &ColorValue) -> Vec<String> {
+    Notation::value_variants().iter().map(|n| format_value(value, *n)).collect()
+}
+
+/// Extract every color value from the given entries (or only from `only`). Variables are
+/// resolved across all entries, so `$accent` defined in a theme colors keys elsewhere.
+pub fn extract(entries: &[ConfigEntry], only: Option<&Path>) -> Vec<PaletteEntry> {
+    let parsed: Vec<(&ConfigEntry, Vec<parse::Line>)> = entries
+        .iter()
+        .filter(|e| e.category != Category::Scripts)
+        .filter_map(|e| parse::parse_file(&e.path).ok().map(|lines| (e, lines)))
+        .collect();
+    let vars: HashMap<String, String> = parse::variables(parsed.iter().flat_map(|(_, l)| l.iter()));
+
+    let mut out = Vec::new();
+    for (entry, lines) in &parsed {
+        if only.is_some_and(|p| p != entry.path) {
+            continue;
+        }
+        for line in lines {
+            let LineKind::Assign { key, value, .. } = &line.kind else { continue };
+            let name = line.option_path().unwrap_or_else(|| key.clone());
+            let resolved = parse::resolve_vars(value, &vars);
+            if let Some(value) = parse_color_value(&resolved) {
+                out.push(PaletteEntry {
+                    name,
+                    path: entry.path.clone(),
+                    line: line.number,
+                    raw: resolved,
+                    value,
+                });
+            }
+        }
+    }
+    out
+}
+
+/// Render a truecolor swatch for plain terminal output.
+pub fn ansi_swatch(value: &ColorValue, width: usize) -> String {
+    let per = (width / value.stops.len()).max(1);
+    let mut out = String::new();
+    for c in &value.stops {
+        out.push_str(&format!("\x1b[48;2;{};{};{}m{}", c.r, c.g, c.b, " ".repeat(per)));
+    }
+    out.push_str("\x1b[0m");
+    out
+}
+
+pub fn convert(input: &str, to: Option<Notation>) -> Result<Vec<String>> {
+    let Some(value) = parse_color_value(input) else {
+        bail!("not a color: {input}");
+    };
+    Ok(match to {
+        Some(n) => vec![format_value(&value, n)],
+        None => conversions(&value),
+    })
+}
+
+fn resolve_scope(entries: &[ConfigEntry], name: &str) -> Result<PathBuf> {
+    let by_alias = |cat: Option<Category>| {
+        entries
+            .iter()
+            .find(|e| e.alias == name && cat.is_none_or(|c| e.category == c))
+            .map(|e| e.path.clone())
+    };
+    if let Some(p) = by_alias(Some(Category::Themes)).or_else(|| by_alias(None)) {
+        return Ok(p);
+    }
+    let p = PathBuf::from(name);
+    if p.is_file() {
+        return Ok(p);
+    }
+    bail!("no theme or file named {name}")
+}
+
+pub fn run(args: &PaletteArgs, entries: &[ConfigEntry], picker: &Picker) -> Result<()> {
+    if let Some(input) = &args.convert {
+        for line in convert(input, args.to)? {
+            println!("{line}");
+        }
+        return Ok(());
+    }
+
+    let only = args.theme.as_deref().map(|t| resolve_scope(entries, t)).transpose()?;
+    let colors = extract(entries, only.as_deref());
+    if colors.is_empty() {
+        bail!("no colors found");
+    }
+
+    if args.list {
+        for c in &colors {
+            println!(
+                "{} {}  {}  ({}:{})",
+                ansi_swatch(&c.value, 8),
+                c.name,
+                c.raw,
+                c.path.display(),
+                c.line
+            );
+        }
+        return Ok(());
+    }
+
+    if let Some(sel) = picker.pick_color(&colors)? {
+        match args.to {
+            Some(n) => println!("{}", format_value(&sel.value, n)),
+            None => {
+                for line in conversions(&sel.value) {
+                    println!("{line}");
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::{Error, Result};
+
+/// One physical line of a Hyprland-style config file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineKind {
+    Blank,
+    /// Full comment line, including the leading `#`
+    Comment(String),
+    /// `name {` opening a (possibly nested) section
+    Open { name: String, comment: Option<String> },
+    /// `}` closing the innermost section
+    Close { comment: Option<String> },
+    /// `key = value`; `$var = value` definitions are assignments whose key starts with `$`
+    Assign { key: String, value: String, comment: Option<String> },
+    /// Anything we could not classify; kept verbatim
+    Other(String),
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Line {
+    /// 1-based line number
+    pub number: usize,
+    /// Nesting depth of the line itself; a closing `}` sits at its opener's depth
+    pub depth: usize,
+    /// Enclosing section path joined with `:` (e.g. "decoration:blur"), empty at top level
+    pub section: String,
+    pub kind: LineKind,
+}
+
+impl Line {
+    /// Full option path for assignments, e.g. "decoration:blur:size".
+    pub fn option_path(&self) -> Option<String> {
+        match &self.kind {
+            LineKind::Assign { key, .. } if !key.starts_with('$') => Some(if self.section.is_empty() {
+                key.clone()
+            } else {
+                format!("{}:{}", self.section, key)
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Split off a trailing `# comment`. Hyprland uses `##` to escape a literal `#`.
+fn split_comment(s: &str) -> (String, Option<String>) {
+    let mut out = String::new();
+    let mut chars = s.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c == '#' {
+            if matches!(chars.peek(), Some((_, '#'))) {
+                out.push_str("##");
+                chars.next();
+                continue;
+            }
+            return (out, Some(s[i..].trim_end().to_string()));
+        }
+        out.push(c);
+    }
+    (out, None)
+}
+
+fn classify(raw: &str) -> LineKind {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return LineKind::Blank;
+    }
+    if trimmed.starts_with('#') && !trimmed.starts_with("##") {
+        return LineKind::Comment(trimmed.to_string());
+    }
+    let (body, comment) = split_comment(trimmed);
+    let body = body.trim();
+    if body == "}" {
+        return LineKind::Close { comment };
+    }
+    if let Some(name) = body.strip_suffix('{') {
+        let name = name.trim();
+        if !name.contains('=') {
+            return LineKind::Open { name: name.to_string(), comment };
+        }
+    }
+    if let Some((key, value)) = body.split_once('=') {
+        let key = key.trim();
+        if !key.is_empty() && !key.contains(char::is_whitespace) {
+            return LineKind::Assign { key: key.to_string(), value: value.trim().to_string(), comment };
+        }
+    }
+    LineKind::Other(trimmed.to_string())
+}
+
+/// Classify every line of `src`, tracking the `{}` section each one is in.
+pub fn parse_str(src: &str) -> Vec<Line> {
+    let mut out = Vec::new();
+    let mut stack: Vec<String> = Vec::new();
+    for (i, raw) in src.lines().enumerate() {
+        let kind = classify(raw);
+        if matches!(kind, LineKind::Close { .. }) {
+            stack.pop();
+        }
+        let line = Line { number: i + 1, depth: stack.len(), section: stack.join(":"), kind };
+        if let LineKind::Open { name, .. } = &line.kind {
+            stack.push(name.clone());
+        }
+        out.push(line);
+    }
+    out
+}
+
+pub fn parse_file(path: &Path) -> Result<Vec<Line>> {
+    let src = fs::read_to_string(path).map_err(|e| Error::io("reading", path, e))?;
+    Ok(parse_str(&src))
+}
+
+/// `bind` and its flag variants (`binde`, `bindlr`, ...), but not options such as
+/// `binds:scroll_event_delay`.
+pub fn is_bind_keyword(key: &str) -> bool {
+    key.strip_prefix("bind").is_some_and(|flags| flags.chars().all(|c| c.is_ascii_lowercase()))
+}
+
+/// Collect `$name = value` definitions (later definitions win, as in Hyprland).
+pub fn variables<'a>(lines: impl IntoIterator<Item = &'a Line>) -> HashMap<String, String> {
+    let mut vars = HashMap::new();
+    for line in lines {
+        if let LineKind::Assign { key, value, .. } = &line.kind
+            && let Some(name) = key.strip_prefix('$')
+        {
+            vars.insert(name.to_string(), value.clone());
+        }
+    }
+    vars
+}
+
+/// Expand `$name` references, recursively, leaving unknown variables untouched.
+pub fn resolve_vars(value: &str, vars: &HashMap<String, String>) -> String {
+    resolve_depth(value, vars, 0)
+}
+
+fn resolve_depth(value: &str, vars: &HashMap<String, String>, depth: usize) -> String {
+    if depth > 8 || !value.contains('$') {
+        return value.to_string();
+    }
+    let mut out = String::new();
+    let mut rest = value;
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+        let len = after
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(after.len());
+        let name = &after[..len];
+        match vars.get(name) {
+            Some(v) if !name.is_empty() => out.push_str(&resolve_depth(v, vars, depth + 1)),
+            _ => {
+                out.push('$');
+                out.push_str(name);
+            }
+        }
+        rest = &after[len..];
+    }
+    out.push_str(rest);
+    out
+}
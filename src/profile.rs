@@ -0,0 +1,146 @@
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result, bail};
+
+use crate::{
+    cli::{ProfileArgs, ProfileCommand},
+    config::Profile,
+    model::{Category, ConfigEntry},
+    scan,
+    watch::LiveEntry,
+};
+
+/// File `profile switch` writes the active profile's `source =` lines to, in the root.
+pub const PROFILE_FILE: &str = "profile.conf";
+
+/// First line of the generated file; the rest of the line is the profile name.
+const MARKER: &str = "# hyprconf profile: ";
+
+pub fn hostname() -> Option<String> {
+    ["/proc/sys/kernel/hostname", "/etc/hostname"]
+        .iter()
+        .find_map(|p| fs::read_to_string(p).ok())
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+}
+
+/// The first profile listing this machine's hostname.
+pub fn for_host(profiles: &[Profile]) -> Option<&Profile> {
+    let host = hostname()?;
+    profiles.iter().find(|p| p.hosts.iter().any(|h| h.eq_ignore_ascii_case(&host)))
+}
+
+/// Name of the profile last switched to under `root`.
+pub fn switched(root: &Path) -> Option<String> {
+    let src = fs::read_to_string(root.join(PROFILE_FILE)).ok()?;
+    src.lines().next()?.strip_prefix(MARKER).map(|n| n.trim().to_string())
+}
+
+/// The profile last switched to, else the one for this host.
+pub fn active<'a>(root: &Path, profiles: &'a [Profile]) -> Option<&'a Profile> {
+    match switched(root) {
+        Some(name) => profiles.iter().find(|p| p.name == name),
+        None => for_host(profiles),
+    }
+}
+
+/// `*` and `?` glob over a relative path; neither crosses a `/`.
+fn glob_match(pattern: &str, path: &str) -> bool {
+    let re = regex::escape(pattern).replace(r"\*", "[^/]*").replace(r"\?", "[^/]");
+    regex::Regex::new(&format!("^{re}$")).is_ok_and(|r| r.is_match(path))
+}
+
+fn matches_any(patterns: &[String], path: &str) -> bool {
+    let bare = path.strip_suffix(".conf").unwrap_or(path);
+    patterns.iter().any(|p| glob_match(p, path) || glob_match(p, bare))
+}
+
+/// Whether `profile` turns the entry on; None for categories profiles don't manage.
+pub fn includes(profile: &Profile, root: &Path, e: &ConfigEntry) -> Option<bool> {
+    let (dir, patterns) = match e.category {
+        Category::ConfD => ("conf.d", &profile.modules),
+        Category::Themes => ("themes", &profile.themes),
+        _ => return None,
+    };
+    let rel = e.path.strip_prefix(root.join(dir)).ok()?;
+    Some(matches_any(patterns, &rel.to_string_lossy()))
+}
+
+/// Flag entries with whether the active profile includes them.
+pub fn mark(entries: &mut [LiveEntry], profile: Option<&Profile>, root: &Path) {
+    for e in entries.iter_mut() {
+        e.in_profile = profile.and_then(|p| includes(p, root, &e.entry));
+    }
+}
+
+/// Write `profile`'s modules and themes as `source =` lines; themes first so modules can use
+/// their variables. Returns how many files are sourced.
+fn switch(profile: &Profile, root: &Path, entries: &[ConfigEntry]) -> Result<usize> {
+    for (dir, patterns) in [("conf.d", &profile.modules), ("themes", &profile.themes)] {
+        let files: Vec<String> = entries
+            .iter()
+            .filter_map(|e| e.path.strip_prefix(root.join(dir)).ok())
+            .map(|rel| rel.to_string_lossy().into_owned())
+            .collect();
+        for p in patterns {
+            if !files.iter().any(|f| matches_any(std::slice::from_ref(p), f)) {
+                eprintln!("warning: {p} matches nothing in {dir}/");
+            }
+        }
+    }
+
+    let mut sourced: Vec<&ConfigEntry> = entries.iter().filter(|e| includes(profile, root, e) == Some(true)).collect();
+    sourced.sort_by_key(|e| e.category != Category::Themes);
+    let mut out = format!("{MARKER}{}\n# Written by `hyprconf profile switch`; changes are overwritten\n", profile.name);
+    for e in &sourced {
+        let rel = e.path.strip_prefix(root).unwrap_or(&e.path);
+        out.push_str(&format!("source = ./{}\n", rel.display()));
+    }
+    let file = root.join(PROFILE_FILE);
+    fs::write(&file, out).with_context(|| format!("writing {}", file.display()))?;
+    Ok(sourced.len())
+}
+
+/// `hyprconf profile`: list the profiles or switch to one.
+pub fn run(args: &ProfileArgs, root: &Path, entries: &[ConfigEntry], profiles: &[Profile]) -> Result<()> {
+    if profiles.is_empty() {
+        bail!("no profiles defined; add `profile {{ name = ... }}` sections to hyprconf.conf");
+    }
+    match &args.command {
+        ProfileCommand::List => {
+            let active = active(root, profiles).map(|p| p.name.as_str());
+            let host = for_host(profiles).map(|p| p.name.as_str());
+            for p in profiles {
+                let mark = if Some(p.name.as_str()) == active { "*" } else { " " };
+                let note = if Some(p.name.as_str()) == host { "  (this host)" } else { "" };
+                let count = entries.iter().filter(|e| includes(p, root, e) == Some(true)).count();
+                println!("{mark} {:<16} {count} file(s){note}", p.name);
+            }
+            Ok(())
+        }
+        ProfileCommand::Switch { name } => {
+            let profile = match name {
+                Some(name) => profiles.iter().find(|p| p.name == *name).with_context(|| format!("no profile named {name}"))?,
+                None => for_host(profiles).with_context(|| {
+                    format!("no profile lists host {}", hostname().unwrap_or_else(|| "(unknown)".to_string()))
+                })?,
+            };
+            let sourced = switch(profile, root, entries)?;
+            println!("switched to {}: {sourced} file(s) in {PROFILE_FILE}", profile.name);
+
+            // The file only takes effect once hyprland.conf sources it
+            let order = scan::load_order(root);
+            let loaded = |p: &Path| p.canonicalize().is_ok_and(|p| order.contains(&p));
+            if !loaded(&root.join(PROFILE_FILE)) {
+                println!("add `source = ./{PROFILE_FILE}` to hyprland.conf to load it");
+            }
+            // profile.conf doesn't source these, so something else does
+            let stray = entries.iter().filter(|e| includes(profile, root, e) == Some(false) && loaded(&e.path)).count();
+            if stray > 0 {
+                println!("{stray} file(s) outside the profile are still sourced elsewhere, e.g. by a `source = conf.d/*.conf` line");
+            }
+            Ok(())
+        }
+    }
+}
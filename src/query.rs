@@ -0,0 +1,110 @@
+use crate::model::{Category, ConfigEntry};
+
+/// Which entries the picker lists.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    pub category: Option<Category>,
+    /// Only entries with this header tag
+    pub tag: Option<String>,
+}
+
+impl Filter {
+    pub fn matches(&self, e: &ConfigEntry) -> bool {
+        self.category.is_none_or(|c| e.category == c)
+            && self.tag.as_ref().is_none_or(|t| e.header.tags.iter().any(|x| x.eq_ignore_ascii_case(t)))
+    }
+}
+
+/// Entry fields a picker query can be scoped to with `field:value`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Category,
+    Alias,
+    Tag,
+    Host,
+    Path,
+    Description,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "cat" | "category" => Field::Category,
+            "alias" => Field::Alias,
+            "tag" => Field::Tag,
+            "host" => Field::Host,
+            "path" => Field::Path,
+            "desc" | "description" => Field::Description,
+            _ => return None,
+        })
+    }
+}
+
+/// One `field:value` term; `-field:value` (or `!field:value`) negates it.
+#[derive(Debug, Clone)]
+pub struct Term {
+    pub field: Field,
+    /// Lowercased
+    pub value: String,
+    pub negated: bool,
+}
+
+fn contains(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(needle)
+}
+
+impl Term {
+    /// Case-insensitive substring match, so terms narrow the list while still being typed.
+    fn matches(&self, e: &ConfigEntry) -> bool {
+        let found = match self.field {
+            // Both the displayed name ("conf.d") and the --category one ("conf-d")
+            Field::Category => {
+                contains(&e.category.to_string(), &self.value) || contains(e.category.name(), &self.value)
+            }
+            Field::Alias => contains(&e.alias, &self.value),
+            Field::Tag => e.header.tags.iter().any(|t| contains(t, &self.value)),
+            Field::Host => e.header.hosts.iter().any(|h| contains(h, &self.value)),
+            Field::Path => contains(&e.path.to_string_lossy(), &self.value),
+            Field::Description => e.description.as_deref().is_some_and(|d| contains(d, &self.value)),
+        };
+        found != self.negated
+    }
+}
+
+/// A picker query split into field terms and the text left for skim's matcher.
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    pub terms: Vec<Term>,
+    pub text: String,
+}
+
+impl Query {
+    /// Words of the form `[-]field:value` with a known field become terms; everything else,
+    /// including skim's own syntax (`'exact`, `!not`, `a | b`), stays in `text`. A term without a
+    /// value yet (`cat:`) is dropped.
+    pub fn parse(query: &str) -> Self {
+        let mut out = Query::default();
+        let mut rest: Vec<&str> = Vec::new();
+        for word in query.split_whitespace() {
+            let (negated, body) = match word.strip_prefix(['-', '!']) {
+                Some(body) => (true, body),
+                None => (false, word),
+            };
+            match body.split_once(':').and_then(|(name, value)| Some((Field::from_name(name)?, value))) {
+                Some((_, "")) => {}
+                Some((field, value)) => out.terms.push(Term { field, value: value.to_lowercase(), negated }),
+                None => rest.push(word),
+            }
+        }
+        out.text = rest.join(" ");
+        out
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty() && self.text.is_empty()
+    }
+
+    pub fn matches(&self, e: &ConfigEntry) -> bool {
+        self.terms.iter().all(|t| t.matches(e))
+    }
+}
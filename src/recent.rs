@@ -0,0 +1,40 @@
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::{
+    cli::RecentArgs,
+    edit,
+    history::{self, History, Recent},
+    model::ConfigEntry,
+    ui::Picker,
+};
+
+fn recent_line(r: &Recent, root: &Path) -> String {
+    let path = r.entry.path.strip_prefix(root).unwrap_or(&r.entry.path);
+    let line = r.line.map(|l| format!(":{l}")).unwrap_or_default();
+    format!("{:>9}  [{}] {}  {}{line}", history::ago(r.time), r.entry.category, r.entry.alias, path.display())
+}
+
+/// `hyprconf recent`: the last opened entries, newest first; picking one reopens it.
+pub fn run(args: &RecentArgs, root: &Path, entries: &[ConfigEntry], picker: &Picker, editor: Option<&str>) -> Result<()> {
+    let history = History::load();
+    let recent = history.recent(entries, args.limit);
+    if recent.is_empty() {
+        println!("no file under {} has been opened with hyprconf yet", root.display());
+        return Ok(());
+    }
+    let lines: Vec<String> = recent.iter().map(|r| recent_line(r, root)).collect();
+
+    if args.list {
+        for l in &lines {
+            println!("{l}");
+        }
+        return Ok(());
+    }
+
+    if let Some(i) = picker.pick_index(&lines)? {
+        edit::open_entry(editor, recent[i].entry, recent[i].line, root)?;
+    }
+    Ok(())
+}
@@ -1,33 +1,87 @@
-use std::{fs::File, io::{BufRead, BufReader}, path::{Path, PathBuf}};
+use std::{fs, path::{Path, PathBuf}, sync::Mutex};
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 
-use anyhow::{Context, Result};
+use ignore::{WalkBuilder, WalkState, gitignore::Gitignore};
 
-use crate::model::{Category, ConfigEntry};
+use crate::{
+    Error, Result,
+    cache::{Cache, Meta},
+    dotfiles::Resolver,
+    model::{Category, ConfigEntry, ConfigLine, Header, Summary},
+    parse::{self, LineKind},
+};
 
 const COMMENT_PREFIXES: &[&str] = &["#", "//", ";"]; // common comment styles
 
-fn first_comment_line(path: &Path, max_lines: usize) -> Result<Option<String>> {
-    let file = match File::open(path) {
-        Ok(f) => f,
-        Err(_) => return Ok(None),
-    };
-    let reader = BufReader::new(file);
-    for (i, line) in reader.lines().enumerate() {
-        if i >= max_lines { break; }
-        let Ok(line) = line else { continue };
+const HEADER_KEYS: &[&str] = &["alias", "description", "tags", "host", "requires"];
+
+/// `@key: value` in a comment's content, for known header keys.
+fn header_field(content: &str) -> Option<(&str, &str)> {
+    let (key, value) = content.strip_prefix('@')?.split_once(':')?;
+    let key = key.trim();
+    HEADER_KEYS.contains(&key).then_some((key, value.trim()))
+}
+
+fn header_list(value: &str) -> Vec<String> {
+    value.split(',').map(str::trim).filter(|s| !s.is_empty()).map(String::from).collect()
+}
+
+/// First comment line within `max_lines` (the description), and the header: `@key: value`
+/// comments in the comment block at the top of the file. Header lines are never the description.
+fn read_description(src: &str, max_lines: usize) -> (Option<String>, Header) {
+    let mut desc = None;
+    let mut header = Header::default();
+    let mut in_header = true;
+    for (i, line) in src.lines().enumerate() {
+        if !in_header && (desc.is_some() || i >= max_lines) { break; }
         let trimmed = line.trim();
         // Skip shebangs like #!/bin/bash
         if trimmed.starts_with("#!") { continue; }
-        if let Some(prefix) = COMMENT_PREFIXES.iter().find(|p| trimmed.starts_with(**p)) {
-            let content = trimmed.trim_start_matches(prefix).trim().to_string();
-            if !content.is_empty() {
-                return Ok(Some(content));
+        let Some(prefix) = COMMENT_PREFIXES.iter().find(|p| trimmed.starts_with(**p)) else {
+            // The header block ends at the first line that is not a comment
+            in_header &= trimmed.is_empty();
+            continue;
+        };
+        let content = trimmed.trim_start_matches(prefix).trim();
+        if in_header && let Some((key, value)) = header_field(content) {
+            if value.is_empty() { continue; }
+            match key {
+                "alias" => header.alias = Some(value.to_string()),
+                "description" => header.description = Some(value.to_string()),
+                "tags" => header.tags.extend(header_list(value)),
+                "host" => header.hosts.extend(header_list(value)),
+                _ => header.requires.extend(header_list(value)),
             }
+        } else if desc.is_none() && i < max_lines && !content.is_empty() {
+            desc = Some(content.to_string());
+        }
+    }
+    (desc, header)
+}
+
+/// Line counts of a file; scripts are not Hyprland syntax, so they only get `lines`.
+fn summarize(src: &str, category: Category) -> Summary {
+    let mut summary = Summary { lines: src.lines().count(), ..Summary::default() };
+    if category == Category::Scripts {
+        return summary;
+    }
+    for line in parse::parse_str(src) {
+        let LineKind::Assign { key, .. } = &line.kind else { continue };
+        match key.as_str() {
+            k if k.starts_with('$') => summary.variables += 1,
+            k if parse::is_bind_keyword(k) => summary.binds += 1,
+            "source" => summary.sources += 1,
+            _ => summary.assignments += 1,
         }
     }
-    Ok(None)
+    summary
+}
+
+fn read_meta(bytes: &[u8], max_lines: usize, category: Category) -> Meta {
+    let src = String::from_utf8_lossy(bytes);
+    let (description, header) = read_description(&src, max_lines);
+    Meta { description, header, summary: summarize(&src, category) }
 }
 
 fn alias_from_conf_d(file_stem: &str) -> String {
@@ -39,7 +93,7 @@ fn alias_from_conf_d(file_stem: &str) -> String {
     }
 }
 
-fn entry_for_path(path: PathBuf, category: Category) -> Result<ConfigEntry> {
+fn entry_for_path(path: PathBuf, category: Category, cache: Option<&Cache>, dotfiles: &Resolver) -> Result<ConfigEntry> {
     let file_name = path
         .file_name()
         .map(|s| s.to_string_lossy().into_owned())
@@ -48,37 +102,45 @@ fn entry_for_path(path: PathBuf, category: Category) -> Result<ConfigEntry> {
         .file_stem()
         .map(|s| s.to_string_lossy().into_owned())
         .unwrap_or_default();
+    // First comment line, header and summary, from the cache when the file is unchanged
+    let describe = |max_lines: usize| -> Meta {
+        let read = |bytes: &[u8]| read_meta(bytes, max_lines, category);
+        match cache {
+            Some(c) => c.describe(&path, read),
+            None => read(&fs::read(&path).unwrap_or_default()),
+        }
+    };
 
-    let (alias, mut desc) = match category {
+    let (mut alias, Meta { description: mut desc, header, summary }) = match category {
         Category::Hyprland => {
             let alias = "hyprland".to_string();
-            let desc = first_comment_line(&path, 10)?;
+            let desc = describe(10);
             (alias, desc)
         }
         Category::Utility => {
             let alias = stem.clone();
-            let desc = first_comment_line(&path, 20)?;
+            let desc = describe(20);
             (alias, desc)
         }
         Category::ConfD => {
             let alias = alias_from_conf_d(&stem);
-            let desc = first_comment_line(&path, 10)?;
+            let desc = describe(10);
             (alias, desc)
         }
         Category::Themes => {
             let alias = stem.clone();
-            let desc = first_comment_line(&path, 10)?;
+            let desc = describe(10);
             (alias, desc)
         }
         Category::Plugins => {
             let alias = stem.clone();
-            let desc = first_comment_line(&path, 10)?;
+            let desc = describe(10);
             (alias, desc)
         }
         Category::Scripts => {
             // Use file stem if available, else the full file name
             let alias = stem.clone();
-            let desc = first_comment_line(&path, 10)?;
+            let desc = describe(10);
             (alias, desc)
         }
     };
@@ -88,13 +150,24 @@ fn entry_for_path(path: PathBuf, category: Category) -> Result<ConfigEntry> {
         let stripped = strip_alias_prefix(&alias, d);
         desc = Some(stripped);
     }
+    // Header values win over derived ones
+    if let Some(a) = &header.alias {
+        alias = a.clone();
+    }
+    if let Some(d) = &header.description {
+        desc = Some(d.clone());
+    }
 
+    let link = dotfiles.inspect(&path);
     Ok(ConfigEntry {
         path,
         file_name,
         alias,
         description: desc,
         category,
+        link,
+        header,
+        summary,
     })
 }
 
@@ -115,91 +188,331 @@ fn strip_alias_prefix(alias: &str, desc: &str) -> String {
     trimmed.to_string()
 }
 
-pub fn scan_configs(root: &Path) -> Result<Vec<ConfigEntry>> {
-    let mut out: Vec<ConfigEntry> = Vec::new();
+/// Name of the gitignore-syntax file that hides paths from scanning. One at the root applies
+/// to every scanned directory; others apply below the directory they live in.
+pub const IGNORE_FILE: &str = ".hyprconfignore";
 
-    // 1) hyprland.conf
-    let hyprland = root.join("hyprland.conf");
-    if hyprland.exists() {
-        out.push(entry_for_path(hyprland, Category::Hyprland)?);
+/// How a [`Scanner`] walks the category directories.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ScanOptions {
+    /// Directory levels to descend below conf.d/, themes/, plugins/ and scripts/ (1 = top level only)
+    pub max_depth: usize,
+    /// Include hidden files and directories
+    pub hidden: bool,
+    /// Walker threads; 0 picks one per CPU
+    pub threads: usize,
+    /// Reuse descriptions from the on-disk cache for unchanged files
+    pub cache: bool,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self { max_depth: 3, hidden: false, threads: 0, cache: true }
     }
+}
 
-    // 2) Utilities: hyprpaper.conf, hyprlock.conf, hypridle.conf
-    for util in ["hyprpaper.conf", "hyprlock.conf", "hypridle.conf"] {
-        let p = root.join(util);
-        if p.exists() {
-            out.push(entry_for_path(p, Category::Utility)?);
-        }
+fn error_path(err: &ignore::Error) -> Option<&Path> {
+    match err {
+        ignore::Error::WithPath { path, .. } => Some(path),
+        ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => error_path(err),
+        _ => None,
+    }
+}
+
+fn is_broken_link(path: &Path) -> bool {
+    path.symlink_metadata().is_ok_and(|m| m.file_type().is_symlink()) && !path.exists()
+}
+
+/// Prefix an alias with the entry's subdirectory below its category dir, e.g. "laptop/binds".
+/// An alias from the file's header is kept as is.
+fn nest_alias(mut entry: ConfigEntry, dir: &Path) -> ConfigEntry {
+    if entry.header.alias.is_none()
+        && let Some(sub) = entry.path.parent().and_then(|p| p.strip_prefix(dir).ok())
+        && !sub.as_os_str().is_empty()
+    {
+        entry.alias = format!("{}/{}", sub.display(), entry.alias);
+    }
+    entry
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    std::fs::metadata(path).is_ok_and(|m| (m.permissions().mode() & 0o111) != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    true // on non-unix, include files
+}
+
+/// Category of a file found below one of the scanned subdirectories, with that directory.
+fn classify(root: &Path, path: &Path) -> Option<(Category, PathBuf)> {
+    let (sub, category) = [
+        ("conf.d", Category::ConfD),
+        ("themes", Category::Themes),
+        ("plugins", Category::Plugins),
+        ("scripts", Category::Scripts),
+    ]
+    .into_iter()
+    .find(|(sub, _)| path.starts_with(root.join(sub)))?;
+    let wanted = match category {
+        Category::Scripts => is_executable(path),
+        _ => path.extension().is_some_and(|e| e == "conf"),
+    };
+    wanted.then(|| (category, root.join(sub)))
+}
+
+/// Finds and describes the config files below a Hypr config directory.
+///
+/// ```no_run
+/// # use hyprconf::scan::Scanner;
+/// let entries = Scanner::new("/home/me/.config/hypr").hidden(true).cache(false).scan()?;
+/// # Ok::<(), hyprconf::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Scanner {
+    root: PathBuf,
+    options: ScanOptions,
+}
+
+impl Scanner {
+    /// A scanner for `root` with default options.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into(), options: ScanOptions::default() }
     }
 
-    // 3) conf.d/*.conf
-    let confd = root.join("conf.d");
-    if confd.is_dir() {
-        for entry in std::fs::read_dir(&confd).with_context(|| format!("reading {}", confd.display()))? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.extension().map(|e| e == "conf").unwrap_or(false) {
-                out.push(entry_for_path(path, Category::ConfD)?);
+    /// Replace all options at once, e.g. with ones read from a settings file.
+    pub fn options(mut self, options: ScanOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// See [`ScanOptions::max_depth`].
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.options.max_depth = max_depth;
+        self
+    }
+
+    /// See [`ScanOptions::hidden`].
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.options.hidden = hidden;
+        self
+    }
+
+    /// See [`ScanOptions::threads`].
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.options.threads = threads;
+        self
+    }
+
+    /// See [`ScanOptions::cache`].
+    pub fn cache(mut self, cache: bool) -> Self {
+        self.options.cache = cache;
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Every entry below the root, in [`ConfigEntry::sort_key`] order.
+    pub fn scan(&self) -> Result<Vec<ConfigEntry>> {
+        self.scan_with(|_| {})
+    }
+
+    /// Like [`scan`](Self::scan), also handing each entry to `sink` as soon as it is read.
+    /// Subdirectories are walked in parallel, so `sink` sees entries in no particular order.
+    pub fn scan_with(&self, sink: impl Fn(&ConfigEntry) + Sync) -> Result<Vec<ConfigEntry>> {
+        let (root, opts) = (self.root.as_path(), &self.options);
+        let cache = if opts.cache { Cache::load(root).ok() } else { None };
+        let dotfiles = Resolver::new();
+        let out: Mutex<Vec<ConfigEntry>> = Mutex::new(Vec::new());
+        let first_error: Mutex<Option<Error>> = Mutex::new(None);
+        let push = |entry: Result<ConfigEntry>| match entry {
+            Ok(e) => {
+                sink(&e);
+                out.lock().unwrap().push(e);
+            }
+            Err(err) => {
+                first_error.lock().unwrap().get_or_insert(err);
+            }
+        };
+
+        // 1) hyprland.conf, 2) utilities: hyprpaper.conf, hyprlock.conf, hypridle.conf
+        for (name, category) in [
+            ("hyprland.conf", Category::Hyprland),
+            ("hyprpaper.conf", Category::Utility),
+            ("hyprlock.conf", Category::Utility),
+            ("hypridle.conf", Category::Utility),
+        ] {
+            let p = root.join(name);
+            if p.exists() || is_broken_link(&p) {
+                push(entry_for_path(p, category, cache.as_ref(), &dotfiles));
             }
         }
-    }
 
-    // 4) themes/*.conf
-    let themes = root.join("themes");
-    if themes.is_dir() {
-        for entry in std::fs::read_dir(&themes).with_context(|| format!("reading {}", themes.display()))? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.extension().map(|e| e == "conf").unwrap_or(false) {
-                // alias = file stem
-                let file_name = path.file_name().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
-                let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
-                let desc = first_comment_line(&path, 10)?;
-                out.push(ConfigEntry { path, file_name, alias: stem, description: desc, category: Category::Themes });
+        // 3) conf.d, themes and plugins: *.conf, 4) scripts: executable files; all recursively
+        let dirs: Vec<PathBuf> =
+            ["conf.d", "themes", "plugins", "scripts"].iter().map(|d| root.join(d)).filter(|d| d.is_dir()).collect();
+        if let Some((first, rest)) = dirs.split_first() {
+            let (root_ignore, _) = Gitignore::new(root.join(IGNORE_FILE));
+            let mut walk = WalkBuilder::new(first);
+            for dir in rest {
+                walk.add(dir);
             }
+            walk.max_depth(Some(opts.max_depth))
+                .hidden(!opts.hidden)
+                .ignore(false)
+                .git_ignore(false)
+                .git_global(false)
+                .git_exclude(false)
+                .parents(false)
+                .follow_links(true)
+                .threads(opts.threads)
+                .add_custom_ignore_filename(IGNORE_FILE);
+            walk.build_parallel().run(|| {
+                Box::new(|found| {
+                    let path = match found {
+                        Ok(e) if e.file_type().is_some_and(|t| t.is_file()) => e.into_path(),
+                        // Broken symlinks fail to resolve; keep them so the picker can flag them
+                        Err(err) => match error_path(&err).filter(|p| is_broken_link(p)) {
+                            Some(p) => p.to_path_buf(),
+                            None => return WalkState::Continue,
+                        },
+                        _ => return WalkState::Continue,
+                    };
+                    if root_ignore.matched_path_or_any_parents(&path, false).is_ignore() {
+                        return WalkState::Continue;
+                    }
+                    if let Some((category, dir)) = classify(root, &path) {
+                        push(entry_for_path(path, category, cache.as_ref(), &dotfiles).map(|e| nest_alias(e, &dir)));
+                    }
+                    WalkState::Continue
+                })
+            });
+        }
+
+        // Exclude everything else by design
+
+        if let Some(err) = first_error.into_inner().unwrap() {
+            return Err(err);
+        }
+        if let Some(cache) = cache {
+            cache.save();
         }
+        // The walk finishes in any order; `sort_key` is total, so the result is the same each run
+        let mut out = out.into_inner().unwrap();
+        out.sort_by_key(|e| e.sort_key());
+        Ok(out)
     }
+}
 
-    // 5) plugins/*.conf
-    let plugins = root.join("plugins");
-    if plugins.is_dir() {
-        for entry in std::fs::read_dir(&plugins).with_context(|| format!("reading {}", plugins.display()))? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.extension().map(|e| e == "conf").unwrap_or(false) {
-                let file_name = path.file_name().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
-                let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
-                let desc = first_comment_line(&path, 10)?;
-                out.push(ConfigEntry { path, file_name, alias: stem, description: desc, category: Category::Plugins });
+/// Every non-blank line of the given entries, for line-search mode.
+pub fn scan_lines(entries: &[ConfigEntry]) -> Vec<ConfigLine> {
+    let mut out = Vec::new();
+    for e in entries {
+        let Ok(src) = std::fs::read_to_string(&e.path) else { continue };
+        for (line, raw) in parse::parse_str(&src).into_iter().zip(src.lines()) {
+            if matches!(line.kind, parse::LineKind::Blank) {
+                continue;
             }
+            out.push(ConfigLine {
+                path: e.path.clone(),
+                line: line.number,
+                text: raw.trim().to_string(),
+                option: line.option_path(),
+            });
         }
     }
+    out
+}
 
-    // 6) scripts/* (executable files)
-    let scripts = root.join("scripts");
-    if scripts.is_dir() {
-        for entry in std::fs::read_dir(&scripts).with_context(|| format!("reading {}", scripts.display()))? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_file() {
-                let meta = std::fs::metadata(&path)?;
-                #[cfg(unix)]
-                let is_exec = (meta.permissions().mode() & 0o111) != 0;
-                #[cfg(not(unix))]
-                let is_exec = true; // on non-unix, include files
-                if is_exec {
-                    let file_name = path.file_name().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
-                    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or(file_name.clone()).to_string();
-                    let desc = first_comment_line(&path, 10)?;
-                    out.push(ConfigEntry { path, file_name, alias: stem, description: desc, category: Category::Scripts });
-                }
+/// Match a single path component against a `*`/`?` glob.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let re = regex::escape(pattern).replace(r"\*", ".*").replace(r"\?", ".");
+    regex::Regex::new(&format!("^{re}$")).is_ok_and(|r| r.is_match(name))
+}
+
+/// Expand a `source = ...` value relative to the sourcing file, honoring `~` and
+/// globs in the file name (e.g. `conf.d/*.conf`).
+fn expand_source(value: &str, base: &Path) -> Vec<PathBuf> {
+    let value = value.trim();
+    let path = match value.strip_prefix("~/") {
+        Some(rest) => match std::env::var("HOME") {
+            Ok(home) => PathBuf::from(home).join(rest),
+            Err(_) => return Vec::new(),
+        },
+        None => base.join(value),
+    };
+    let name = path.file_name().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    if !name.contains(['*', '?']) {
+        return vec![path];
+    }
+    let Some(dir) = path.parent() else { return Vec::new() };
+    let Ok(read) = std::fs::read_dir(dir) else { return Vec::new() };
+    let mut out: Vec<PathBuf> = read
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.file_name().is_some_and(|n| glob_match(&name, &n.to_string_lossy())))
+        .collect();
+    out.sort();
+    out
+}
+
+/// Files the `source =` lines of `path` point at, in order; not followed any further.
+pub fn sources(path: &Path) -> Vec<PathBuf> {
+    let Ok(lines) = parse::parse_file(path) else { return Vec::new() };
+    let base = path.parent().unwrap_or(Path::new("."));
+    lines
+        .iter()
+        .filter_map(|line| match &line.kind {
+            parse::LineKind::Assign { key, value, .. } if key == "source" => Some(expand_source(value, base)),
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
+fn visit_sources(path: &Path, seen: &mut Vec<PathBuf>, out: &mut Vec<(PathBuf, parse::Line)>) {
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if seen.contains(&canonical) {
+        return;
+    }
+    seen.push(canonical.clone());
+    let Ok(lines) = parse::parse_file(path) else { return };
+    let base = path.parent().unwrap_or(Path::new("."));
+    for line in lines {
+        if let parse::LineKind::Assign { key, value, .. } = &line.kind
+            && key == "source"
+        {
+            for p in expand_source(value, base) {
+                visit_sources(&p, seen, out);
             }
         }
+        out.push((canonical.clone(), line));
     }
+}
 
-    // Exclude everything else by design
+/// Every line Hyprland evaluates, in evaluation order: `hyprland.conf` with each
+/// `source =` expanded in place. Paths are canonical.
+pub fn evaluation_order(root: &Path) -> Vec<(PathBuf, parse::Line)> {
+    let mut seen = Vec::new();
+    let mut out = Vec::new();
+    let main = root.join("hyprland.conf");
+    if main.exists() {
+        visit_sources(&main, &mut seen, &mut out);
+    }
+    out
+}
 
-    // Stable ordering: category order, then alias
-    out.sort_by_key(|e| e.sort_key());
-    Ok(out)
+/// Files in the order Hyprland first reads them (canonical paths). Scanned files that are
+/// never sourced are not included.
+pub fn load_order(root: &Path) -> Vec<PathBuf> {
+    let mut order: Vec<PathBuf> = Vec::new();
+    for (path, _) in evaluation_order(root) {
+        if !order.contains(&path) {
+            order.push(path);
+        }
+    }
+    order
 }
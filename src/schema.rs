@@ -0,0 +1,233 @@
+use std::{collections::BTreeMap, fmt, fs, path::Path};
+
+use crate::{Error, Result, color};
+
+/// The schema shipped with the binary; see `schema/options.txt` for the format.
+const BUNDLED: &str = include_str!("../schema/options.txt");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum OptionType {
+    Int,
+    Float,
+    Bool,
+    Color,
+    Gradient,
+    Vec2,
+    #[cfg_attr(feature = "serde", serde(rename = "string"))]
+    Str,
+    Enum,
+}
+
+impl fmt::Display for OptionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            OptionType::Int => "int",
+            OptionType::Float => "float",
+            OptionType::Bool => "bool",
+            OptionType::Color => "color",
+            OptionType::Gradient => "gradient",
+            OptionType::Vec2 => "vec2",
+            OptionType::Str => "string",
+            OptionType::Enum => "enum",
+        };
+        write!(f, "{s}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Constraint {
+    Range { min: Option<f64>, max: Option<f64> },
+    OneOf(Vec<String>),
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Constraint::Range { min, max } => {
+                let side = |v: &Option<f64>| v.map(|v| v.to_string()).unwrap_or_default();
+                write!(f, "{}..{}", side(min), side(max))
+            }
+            Constraint::OneOf(values) => write!(f, "{}", values.join(" | ")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OptionSpec {
+    /// Full section path, e.g. "decoration:blur:size"
+    pub path: String,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub kind: OptionType,
+    pub default: String,
+    pub constraint: Option<Constraint>,
+    pub added: Option<String>,
+    pub removed: Option<String>,
+    pub description: String,
+}
+
+impl OptionSpec {
+    /// Multi-line documentation block for previews and `explain`.
+    pub fn doc(&self) -> String {
+        let mut out = format!("{}  ({}", self.path, self.kind);
+        if !self.default.is_empty() {
+            out.push_str(&format!(", default {}", self.default));
+        }
+        if let Some(c) = &self.constraint {
+            out.push_str(&format!(", {c}"));
+        }
+        out.push_str(")\n");
+        if let Some(v) = &self.added {
+            out.push_str(&format!("added in {v}\n"));
+        }
+        if let Some(v) = &self.removed {
+            out.push_str(&format!("removed in {v}\n"));
+        }
+        out.push('\n');
+        out.push_str(&self.description);
+        out.push('\n');
+        out
+    }
+
+    /// Check a (variable-resolved) value; returns a human-readable problem if it doesn't fit.
+    pub fn check_value(&self, value: &str) -> Option<String> {
+        let v = value.trim();
+        let ok = match self.kind {
+            OptionType::Int => v.parse::<i64>().is_ok() || v.starts_with("0x"),
+            OptionType::Float => v.parse::<f64>().is_ok(),
+            OptionType::Bool => matches!(v, "true" | "false" | "yes" | "no" | "on" | "off" | "0" | "1"),
+            OptionType::Color => color::parse_color(v).is_some(),
+            OptionType::Gradient => color::parse_color_value(v).is_some(),
+            OptionType::Vec2 => {
+                let parts: Vec<&str> = v.split_whitespace().collect();
+                parts.len() == 2 && parts.iter().all(|p| p.parse::<f64>().is_ok())
+            }
+            OptionType::Str | OptionType::Enum => true,
+        };
+        if !ok {
+            return Some(format!("expected {}, got {v:?}", self.kind));
+        }
+        match &self.constraint {
+            Some(Constraint::Range { min, max }) => {
+                let n = v.parse::<f64>().ok()?;
+                if min.is_some_and(|m| n < m) || max.is_some_and(|m| n > m) {
+                    return Some(format!("{v} is outside {}", self.constraint.as_ref()?));
+                }
+            }
+            Some(Constraint::OneOf(values)) if !values.iter().any(|a| a == v) => {
+                return Some(format!("{v:?} is not one of {}", self.constraint.as_ref()?));
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Schema {
+    /// Hyprland version the schema describes
+    pub version: String,
+    options: BTreeMap<String, OptionSpec>,
+}
+
+fn none_if_empty(s: &str) -> Option<String> {
+    if s.is_empty() { None } else { Some(s.to_string()) }
+}
+
+fn parse_type(s: &str) -> Result<OptionType, String> {
+    Ok(match s {
+        "int" => OptionType::Int,
+        "float" => OptionType::Float,
+        "bool" => OptionType::Bool,
+        "color" => OptionType::Color,
+        "gradient" => OptionType::Gradient,
+        "vec2" => OptionType::Vec2,
+        "string" => OptionType::Str,
+        "enum" => OptionType::Enum,
+        _ => return Err(format!("unknown option type {s:?}")),
+    })
+}
+
+fn parse_constraint(s: &str) -> Result<Option<Constraint>, String> {
+    if s.is_empty() {
+        return Ok(None);
+    }
+    if let Some((min, max)) = s.split_once("..") {
+        let bound = |b: &str| -> Result<Option<f64>, String> {
+            if b.is_empty() { Ok(None) } else { b.parse().map(Some).map_err(|_| format!("bad bound {b:?}")) }
+        };
+        return Ok(Some(Constraint::Range { min: bound(min)?, max: bound(max)? }));
+    }
+    Ok(Some(Constraint::OneOf(s.split(',').map(|v| v.trim().to_string()).collect())))
+}
+
+impl Schema {
+    pub fn bundled() -> Self {
+        Self::parse(BUNDLED).expect("bundled schema is valid")
+    }
+
+    /// Load a schema file from disk, e.g. one updated for a newer Hyprland.
+    pub fn load(path: &Path) -> Result<Self> {
+        let src = fs::read_to_string(path).map_err(|e| Error::io("reading", path, e))?;
+        Self::parse(&src).map_err(|e| match e {
+            Error::Schema { line, message, .. } => Error::Schema { path: Some(path.to_path_buf()), line, message },
+            e => e,
+        })
+    }
+
+    pub fn parse(src: &str) -> Result<Self> {
+        let mut version = String::from("unknown");
+        let mut options = BTreeMap::new();
+        for (i, line) in src.lines().enumerate() {
+            let err = |message| Error::Schema { path: None, line: i + 1, message };
+            let line = line.trim();
+            if let Some(comment) = line.strip_prefix('#') {
+                if let Some(v) = comment.trim().strip_prefix("version:") {
+                    version = v.trim().to_string();
+                }
+                continue;
+            }
+            if line.is_empty() {
+                continue;
+            }
+            let cols: Vec<&str> = line.splitn(7, '|').map(str::trim).collect();
+            if cols.len() != 7 {
+                return Err(err(format!("expected 7 columns, got {}", cols.len())));
+            }
+            let spec = OptionSpec {
+                path: cols[0].to_string(),
+                kind: parse_type(cols[1]).map_err(err)?,
+                default: cols[2].to_string(),
+                constraint: parse_constraint(cols[3]).map_err(err)?,
+                added: none_if_empty(cols[4]),
+                removed: none_if_empty(cols[5]),
+                description: cols[6].to_string(),
+            };
+            options.insert(spec.path.clone(), spec);
+        }
+        Ok(Self { version, options })
+    }
+
+    /// The schema file from settings if configured, else the bundled one.
+    pub fn from_settings(path: Option<&Path>) -> Result<Self> {
+        match path {
+            Some(p) => Self::load(p),
+            None => Ok(Self::bundled()),
+        }
+    }
+
+    pub fn get(&self, path: &str) -> Option<&OptionSpec> {
+        self.options.get(path)
+    }
+
+    /// Whether the schema describes any option inside `section`; unknown keys are only
+    /// reported for sections we know, so a partial schema doesn't flag everything.
+    pub fn knows_section(&self, section: &str) -> bool {
+        let prefix = format!("{section}:");
+        self.options.range(prefix.clone()..).next().is_some_and(|(k, _)| k.starts_with(&prefix))
+    }
+}
@@ -1,78 +1,339 @@
-use std::borrow::Cow;
-use anyhow::Result;
-use skim::prelude::*;
+use std::{
+    borrow::Cow,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Mutex, atomic::AtomicBool},
+    thread,
+    time::Duration,
+};
+use anyhow::{Context, Result};
+use notify::RecommendedWatcher;
+use skim::{prelude::*, reader::CommandCollector};
 use skim_tuikit::prelude::{Attr, Color, Effect};
 
-use crate::model::{Category, ConfigEntry};
+use crate::{
+    bookmarks::{self, Bookmarks},
+    config::Profile,
+    git::{self, GitStatus},
+    history::{self, History, SortMode},
+    model::{Category, ConfigEntry, ConfigLine},
+    palette::PaletteEntry,
+    profile,
+    query::{Filter, Query},
+    scan::Scanner,
+    schema::Schema,
+    watch::{self, LiveEntry, Slot, Slots},
+};
 
 pub struct Picker {
-    pub category: Option<Category>,
+    pub filter: Filter,
+    /// Active profile, whose entries the file picker marks
+    pub profile: Option<Profile>,
     pub color_spec: Option<String>,
     pub seg_colors: bool,
 }
 
+/// What the user asked for when leaving line-search mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineAction {
+    Open,
+    /// ctrl-e: explain the option on the line
+    Explain,
+    /// ctrl-b: git blame and value history for the line
+    Blame,
+    /// ctrl-t: add or remove a bookmark on the line
+    Bookmark,
+}
+
+/// What the user asked for when leaving the file picker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryAction {
+    Open,
+    /// ctrl-s: `git add` the file
+    Stage,
+    /// ctrl-o: commit the file with a prompted message
+    Commit,
+    /// ctrl-x: restore the file from HEAD
+    Discard,
+    /// alt-s: switch to the next sort mode
+    Sort,
+    /// ctrl-t: pin or unpin the entry
+    Pin,
+}
+
+pub struct EntryPick {
+    pub entry: ConfigEntry,
+    /// Git status shown for the entry, if it is inside a work tree
+    pub git: Option<GitStatus>,
+    pub action: EntryAction,
+    /// Query at exit, to restore when returning to the picker
+    pub query: String,
+    /// Category tab at exit; None is "all"
+    pub category: Option<Category>,
+}
+
+pub struct LinePick {
+    pub line: ConfigLine,
+    pub action: LineAction,
+    /// Query at exit, to restore when returning to the picker
+    pub query: String,
+}
+
 struct ColoredItem {
     text: String,
     display: AnsiString<'static>,
     id_path: String,
     index: usize,
+    preview: Option<String>,
 }
 
 impl SkimItem for ColoredItem {
-    fn text(&self) -> Cow<str> { Cow::Borrowed(&self.text) }
-    fn output(&self) -> Cow<str> { Cow::Borrowed(&self.id_path) }
+    fn text(&self) -> Cow<'_, str> { Cow::Borrowed(&self.text) }
+    fn output(&self) -> Cow<'_, str> { Cow::Borrowed(&self.id_path) }
     fn get_index(&self) -> usize { self.index }
     fn set_index(&mut self, index: usize) { self.index = index; }
 
+    fn preview(&self, _context: PreviewContext) -> ItemPreview {
+        match &self.preview {
+            Some(text) => ItemPreview::AnsiText(text.clone()),
+            None => ItemPreview::Global,
+        }
+    }
+
     fn display<'a>(&'a self, context: DisplayContext<'a>) -> AnsiString<'a> {
-        // Start with our colored segments, then overlay highlight for matches
-        let mut ret = self.display.clone();
-        let new_fragments: Vec<(Attr, (u32, u32))> = match context.matches {
-            Matches::CharIndices(indices) => indices
-                .iter()
-                .map(|&idx| (context.highlight_attr, (idx as u32, idx as u32 + 1)))
-                .collect(),
-            Matches::CharRange(start, end) => vec![(context.highlight_attr, (start as u32, end as u32))],
-            Matches::ByteRange(start, end) => {
-                let ch_start = context.text[..start].chars().count();
-                let ch_end = ch_start + context.text[start..end].chars().count();
-                vec![(context.highlight_attr, (ch_start as u32, ch_end as u32))]
-            }
-            Matches::None => vec![],
-        };
-        ret.override_attrs(new_fragments);
-        ret
+        highlight(self.display.clone(), context)
     }
 }
 
-impl Picker {
-    pub fn new(category: Option<Category>, color_spec: Option<String>, seg_colors: bool) -> Self {
-        Self { category, color_spec, seg_colors }
+/// File-picker item. The entry is read on every call so watch mode can update it in place.
+#[derive(Clone)]
+struct LiveItem {
+    slot: Slot,
+    /// Position in the slot list, reported as output
+    id: usize,
+    index: usize,
+    seg_colors: bool,
+    /// Characters matched by the query text, highlighted on display
+    matches: Vec<usize>,
+}
+
+impl LiveItem {
+    fn line(&self) -> (String, AnsiString<'static>) {
+        let live = self.slot.read().unwrap();
+        if !live.removed {
+            return build_colored_line(&live, self.seg_colors);
+        }
+        let (text, _) = build_colored_line(&live, false);
+        let text = format!("[deleted] {text}");
+        let attr = Attr { fg: Color::AnsiValue(8), bg: Color::Default, effect: Effect::empty() };
+        let len = text.chars().count() as u32;
+        (text.clone(), AnsiString::new_string(text, vec![(attr, (0, len))]))
+    }
+}
+
+impl SkimItem for LiveItem {
+    fn text(&self) -> Cow<'_, str> { Cow::Owned(self.line().0) }
+    fn output(&self) -> Cow<'_, str> { Cow::Owned(self.id.to_string()) }
+    fn get_index(&self) -> usize { self.index }
+    fn set_index(&mut self, index: usize) { self.index = index; }
+
+    fn preview(&self, _context: PreviewContext) -> ItemPreview {
+        let live = self.slot.read().unwrap();
+        let summary = format!("{}\n", live.entry.summary);
+        match live.git {
+            Some(status) => ItemPreview::AnsiText(format!("{summary}\n{}", git::diff(&live.entry, status))),
+            None => ItemPreview::AnsiText(summary),
+        }
     }
 
-    pub fn pick(&self, entries: &mut [ConfigEntry]) -> Result<Option<ConfigEntry>> {
-        // Pre-filter by category if requested
-        let filtered: Vec<&ConfigEntry> = entries
+    fn display<'a>(&'a self, mut context: DisplayContext<'a>) -> AnsiString<'a> {
+        // Matching happens in `EntryCollector`, so skim itself reports no matches
+        if matches!(context.matches, Matches::None) {
+            context.matches = Matches::CharIndices(&self.matches);
+        }
+        highlight(self.line().1, context)
+    }
+}
+
+/// Selected category tab of the file picker; None is "all".
+type Tab = Arc<Mutex<Option<Category>>>;
+
+/// The tabs in display order.
+fn tabs() -> Vec<Option<Category>> {
+    std::iter::once(None).chain(Category::ALL.map(Some)).collect()
+}
+
+/// Prefix of the commands the tab keys reload the collector with, instead of the query.
+const TAB_CMD: &str = "\u{1}tab:";
+
+/// Tab line above the file list. Counts are read on every redraw, so they follow the scan
+/// and the watcher.
+struct TabBar {
+    tab: Tab,
+    slots: Slots,
+}
+
+impl TabBar {
+    fn line(&self) -> (String, AnsiString<'static>) {
+        let current = *self.tab.lock().unwrap();
+        let categories: Vec<Category> = self
+            .slots
+            .lock()
+            .unwrap()
             .iter()
-            .filter(|e| match self.category { Some(c) => e.category == c, None => true })
+            .map(|s| s.read().unwrap())
+            .filter(|live| !live.removed)
+            .map(|live| live.entry.category)
             .collect();
+        let (mut text, mut fragments) = (String::new(), Vec::new());
+        for tab in tabs() {
+            if !text.is_empty() {
+                text.push_str(" | ");
+            }
+            let count = categories.iter().filter(|c| tab.is_none_or(|t| t == **c)).count();
+            let label = format!("{} {count}", tab.map_or("all".to_string(), |c| c.to_string()));
+            if tab == current {
+                let start = text.chars().count() as u32;
+                let attr = Attr { fg: Color::Default, bg: Color::Default, effect: Effect::REVERSE | Effect::BOLD };
+                fragments.push((attr, (start, start + label.chars().count() as u32)));
+            }
+            text.push_str(&label);
+        }
+        text.push_str("  (tab, f1-f7)");
+        (text.clone(), AnsiString::new_string(text, fragments))
+    }
+}
 
-        // Build custom items channel
+impl SkimItem for TabBar {
+    fn text(&self) -> Cow<'_, str> { Cow::Owned(self.line().0) }
+    fn display<'a>(&'a self, _context: DisplayContext<'a>) -> AnsiString<'a> { self.line().1 }
+}
+
+/// How often the collector looks for entries the scan or the watcher added.
+const POLL: Duration = Duration::from_millis(50);
+
+/// Runs the file picker's query. Skim hands over the whole query on every change: field terms
+/// (`cat:themes`, `-tag:input`) filter the entries, and the remaining text goes to skim's own
+/// matcher, which also ranks the result. The tab keys reload it with a `TAB_CMD` instead, and
+/// the last query is run again under the new tab.
+struct EntryCollector {
+    slots: Slots,
+    tab: Tab,
+    /// Last query skim handed over
+    query: String,
+    /// The initial scan is complete and the slots are in their final order
+    done: Arc<AtomicBool>,
+    /// Keep serving entries the watcher adds
+    watch: bool,
+    seg_colors: bool,
+}
+
+impl CommandCollector for EntryCollector {
+    fn invoke(&mut self, cmd: &str, components_to_stop: Arc<AtomicUsize>) -> (SkimItemReceiver, Sender<i32>) {
         let (tx, rx): (SkimItemSender, SkimItemReceiver) = unbounded();
+        let (tx_interrupt, rx_interrupt) = bounded(1);
+        match cmd.strip_prefix(TAB_CMD) {
+            Some(switch) => self.switch_tab(switch),
+            None => self.query = cmd.to_string(),
+        }
+        let query = Query::parse(&self.query);
+        let category = *self.tab.lock().unwrap();
+        let (slots, done, watch, seg_colors) = (self.slots.clone(), self.done.clone(), self.watch, self.seg_colors);
+        // Skim clears its header line on every reload, so it goes first each time
+        let _ = tx.send(Arc::new(TabBar { tab: self.tab.clone(), slots: slots.clone() }));
 
-        let enable_seg_colors = self.seg_colors && std::env::var("NO_COLOR").is_err();
-        for (i, e) in filtered.iter().enumerate() {
-            let (text, display) = build_colored_line(e, enable_seg_colors);
-            let item = ColoredItem {
-                id_path: e.path.to_string_lossy().into_owned(),
-                text,
-                display,
-                index: i,
-            };
-            let _ = tx.send(Arc::new(item));
+        components_to_stop.fetch_add(1, Ordering::SeqCst);
+        thread::spawn(move || {
+            let factory = AndOrEngineFactory::new(ExactOrFuzzyEngineFactory::builder().build());
+            let engine = (!query.text.is_empty()).then(|| factory.create_engine_with_case(&query.text, CaseMatching::Smart));
+            let (mut next, mut index) = (0, 0);
+            loop {
+                // Slots are reordered once the scan completes; a filtered list has to wait for that
+                let finished = done.load(Ordering::SeqCst);
+                if finished || query.is_empty() {
+                    let new: Vec<Slot> = slots.lock().unwrap()[next..].to_vec();
+                    let mut hits: Vec<(Rank, LiveItem)> = Vec::new();
+                    for (id, slot) in (next..).zip(new) {
+                        next = id + 1;
+                        let wanted = {
+                            let entry = &slot.read().unwrap().entry;
+                            category.is_none_or(|c| c == entry.category) && query.matches(entry)
+                        };
+                        if !wanted {
+                            continue;
+                        }
+                        let mut item = LiveItem { slot, id, index: 0, seg_colors, matches: Vec::new() };
+                        let rank = match &engine {
+                            Some(engine) => {
+                                let Some(m) = engine.match_item(Arc::new(item.clone())) else { continue };
+                                item.matches = m.range_char_indices(&item.text());
+                                m.rank
+                            }
+                            None => Rank::default(),
+                        };
+                        hits.push((rank, item));
+                    }
+                    // Stable, so ties keep the slot order
+                    hits.sort_by_key(|(rank, _)| *rank);
+                    for (_, mut item) in hits {
+                        item.index = index;
+                        index += 1;
+                        let _ = tx.send(Arc::new(item));
+                    }
+                    if finished && !watch {
+                        break;
+                    }
+                }
+                if rx_interrupt.recv_timeout(POLL).is_ok() {
+                    break;
+                }
+            }
+            components_to_stop.fetch_sub(1, Ordering::SeqCst);
+        });
+        (rx, tx_interrupt)
+    }
+}
+
+impl EntryCollector {
+    /// `next`, `prev`, or a tab's position.
+    fn switch_tab(&mut self, switch: &str) {
+        let tabs = tabs();
+        let mut tab = self.tab.lock().unwrap();
+        let i = tabs.iter().position(|t| *t == *tab).unwrap_or(0);
+        let i = match switch {
+            "next" => (i + 1) % tabs.len(),
+            "prev" => (i + tabs.len() - 1) % tabs.len(),
+            n => n.parse().ok().filter(|n| *n < tabs.len()).unwrap_or(i),
+        };
+        *tab = tabs[i];
+    }
+}
+
+/// Overlay the match highlight on an item's colored segments.
+fn highlight<'a>(mut ret: AnsiString<'a>, context: DisplayContext<'a>) -> AnsiString<'a> {
+    let new_fragments: Vec<(Attr, (u32, u32))> = match context.matches {
+        Matches::CharIndices(indices) => indices
+            .iter()
+            .map(|&idx| (context.highlight_attr, (idx as u32, idx as u32 + 1)))
+            .collect(),
+        Matches::CharRange(start, end) => vec![(context.highlight_attr, (start as u32, end as u32))],
+        Matches::ByteRange(start, end) => {
+            let ch_start = context.text[..start].chars().count();
+            let ch_end = ch_start + context.text[start..end].chars().count();
+            vec![(context.highlight_attr, (ch_start as u32, ch_end as u32))]
         }
-        drop(tx);
+        Matches::None => vec![],
+    };
+    ret.override_attrs(new_fragments);
+    ret
+}
 
+impl Picker {
+    pub fn new(filter: Filter, profile: Option<Profile>, color_spec: Option<String>, seg_colors: bool) -> Self {
+        Self { filter, profile, color_spec, seg_colors }
+    }
+
+    fn options(&self) -> SkimOptions {
         // Build skim options
         let mut builder = SkimOptionsBuilder::default();
         builder
@@ -88,28 +349,305 @@ impl Picker {
         } else if std::env::var("NO_COLOR").is_err() {
             options.color = Some("dark".to_string());
         }
+        options
+    }
+
+    /// File picker; once the scan is complete entries are ordered by `sort`, pinned ones first.
+    /// It opens on the `category` tab; the scan covers every category so the tabs can switch
+    /// without rescanning. The preview (alt-p toggles it) shows each file's line counts; entries
+    /// inside a git work tree also get their diff there, status markers and stage/commit/discard keys.
+    pub fn pick(
+        &self,
+        scanner: &Scanner,
+        sort: SortMode,
+        category: Option<Category>,
+        query: Option<&str>,
+        watch: bool,
+    ) -> Result<Option<EntryPick>> {
+        // Scan in the background so entries show up as they are read. While watching, later
+        // changes stream into the running picker too.
+        let slots: Slots = Arc::default();
+        let done: Arc<AtomicBool> = Arc::default();
+        let scan_error: Arc<Mutex<Option<anyhow::Error>>> = Arc::default();
+        let watcher: Arc<Mutex<Option<RecommendedWatcher>>> = Arc::default();
+        {
+            let filter = Filter { category: None, ..self.filter.clone() };
+            let (scanner, active) = (scanner.clone(), self.profile.clone());
+            let (slots, done, scan_error, watcher) = (slots.clone(), done.clone(), scan_error.clone(), watcher.clone());
+            thread::spawn(move || {
+                let order = |mut entries: Vec<ConfigEntry>| {
+                    history::sort(&mut entries, sort, &History::load(), scanner.root());
+                    let mut live: Vec<LiveEntry> = entries.into_iter().map(LiveEntry::new).collect();
+                    git::annotate(&mut live);
+                    bookmarks::pin_first(&mut live, &Bookmarks::load());
+                    profile::mark(&mut live, active.as_ref(), scanner.root());
+                    live
+                };
+                let result = watch::stream(&scanner, &filter, &slots, order);
+                done.store(true, Ordering::SeqCst);
+                let result = result.and_then(|()| if watch { watch::spawn(scanner, filter, slots).map(Some) } else { Ok(None) });
+                match result {
+                    Ok(w) => *watcher.lock().unwrap() = w,
+                    Err(e) => *scan_error.lock().unwrap() = Some(e),
+                }
+            });
+        }
+
+        let mut options = self.options();
+        // Interactive mode hands every query change to the collector, which parses field terms
+        options.interactive = true;
+        options.cmd = Some("{}".to_string());
+        options.cmd_query = query.map(str::to_string);
+        options.cmd_prompt = String::new();
+        let seg_colors = self.seg_colors && std::env::var("NO_COLOR").is_err();
+        let tab: Tab = Arc::new(Mutex::new(category));
+        let collector = EntryCollector { slots: slots.clone(), tab: tab.clone(), query: String::new(), done, watch, seg_colors };
+        options.cmd_collector = Rc::new(RefCell::new(collector));
+        options.header_lines = 1;
+        // Summary and diff preview, and git actions; the preview starts hidden outside a work tree
+        options.preview = Some(String::new());
+        options.preview_window = if git::work_tree(scanner.root()).is_some() { "right:50%" } else { "right:50%:hidden" }.to_string();
+        options.bind = vec![
+            "alt-p:toggle-preview".to_string(),
+            format!("tab:reload({TAB_CMD}next)"),
+            format!("btab:reload({TAB_CMD}prev)"),
+        ];
+        for i in 0..tabs().len() {
+            options.bind.push(format!("f{}:reload({TAB_CMD}{i})", i + 1));
+        }
+        options.expect = vec![
+            "ctrl-s".to_string(),
+            "ctrl-o".to_string(),
+            "ctrl-x".to_string(),
+            "ctrl-t".to_string(),
+            "alt-s".to_string(),
+        ];
+        let active = self.profile.as_ref().map(|p| format!("  profile: {}", p.name)).unwrap_or_default();
+        options.header = Some(format!("sort: {sort} (alt-s){active}"));
+
+        let out = Skim::run_with(&options, None);
+        drop(watcher);
+        if let Some(err) = scan_error.lock().unwrap().take() {
+            return Err(err);
+        }
+        if let Some(out) = out {
+            if out.is_abort { return Ok(None); }
+            if let Some(selected) = out.selected_items.first()
+                && let Ok(id) = selected.output().parse::<usize>()
+                && let Some(slot) = slots.lock().unwrap().get(id)
+            {
+                let action = match out.final_key {
+                    Key::Ctrl('s') => EntryAction::Stage,
+                    Key::Ctrl('o') => EntryAction::Commit,
+                    Key::Ctrl('x') => EntryAction::Discard,
+                    Key::Ctrl('t') => EntryAction::Pin,
+                    Key::Alt('s') => EntryAction::Sort,
+                    _ => EntryAction::Open,
+                };
+                let live = slot.read().unwrap().clone();
+                let category = *tab.lock().unwrap();
+                return Ok(Some(EntryPick { entry: live.entry, git: live.git, action, query: out.cmd, category }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Palette view: one swatch line per color, returns the chosen entry.
+    pub fn pick_color(&self, colors: &[PaletteEntry]) -> Result<Option<PaletteEntry>> {
+        let (tx, rx): (SkimItemSender, SkimItemReceiver) = unbounded();
+        for (i, c) in colors.iter().enumerate() {
+            let (text, display) = build_swatch_line(c);
+            let item = ColoredItem { id_path: i.to_string(), text, display, index: i, preview: None };
+            let _ = tx.send(Arc::new(item));
+        }
+        drop(tx);
+
+        let out = Skim::run_with(&self.options(), Some(rx));
+        if let Some(out) = out {
+            if out.is_abort { return Ok(None); }
+            if let Some(selected) = out.selected_items.first()
+                && let Ok(i) = selected.output().parse::<usize>()
+            {
+                return Ok(colors.get(i).cloned());
+            }
+        }
+        Ok(None)
+    }
+
+    /// Plain list chooser; returns the index of the chosen line.
+    pub fn pick_index(&self, lines: &[String]) -> Result<Option<usize>> {
+        self.pick_index_with(lines, vec![None; lines.len()])
+    }
+
+    /// List chooser with a preview per line; the preview window only opens when some line has one.
+    pub fn pick_index_with(&self, lines: &[String], previews: Vec<Option<String>>) -> Result<Option<usize>> {
+        let mut options = self.options();
+        if previews.iter().any(Option::is_some) {
+            options.preview = Some(String::new());
+            options.preview_window = "down:30%:wrap".to_string();
+        }
+        let (tx, rx): (SkimItemSender, SkimItemReceiver) = unbounded();
+        for ((i, text), preview) in lines.iter().enumerate().zip(previews) {
+            let item = ColoredItem { id_path: i.to_string(), display: text.clone().into(), text: text.clone(), index: i, preview };
+            let _ = tx.send(Arc::new(item));
+        }
+        drop(tx);
 
         let out = Skim::run_with(&options, Some(rx));
         if let Some(out) = out {
             if out.is_abort { return Ok(None); }
             if let Some(selected) = out.selected_items.first() {
-                // We output the path; lookup entry by path
-                let path_out = selected.output();
-                if let Some(entry) = filtered.iter().find(|e| e.path.to_string_lossy() == *path_out) {
-                    return Ok(Some((*entry).clone()));
+                return Ok(selected.output().parse::<usize>().ok().filter(|i| *i < lines.len()));
+            }
+        }
+        Ok(None)
+    }
+
+    /// File chooser starting in `dir`, e.g. for wallpaper images: lists the files `keep` accepts
+    /// and the subdirectories, plus `../`; choosing a directory moves into it.
+    pub fn pick_path(&self, dir: &Path, keep: impl Fn(&Path) -> bool) -> Result<Option<PathBuf>> {
+        let mut dir = dir.canonicalize().with_context(|| format!("reading {}", dir.display()))?;
+        loop {
+            let mut dirs: Vec<PathBuf> = Vec::new();
+            let mut files: Vec<PathBuf> = Vec::new();
+            for p in fs::read_dir(&dir).with_context(|| format!("reading {}", dir.display()))?.flatten().map(|e| e.path()) {
+                if p.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.')) {
+                    continue;
+                }
+                if p.is_dir() {
+                    dirs.push(p);
+                } else if keep(&p) {
+                    files.push(p);
                 }
             }
+            dirs.sort();
+            files.sort();
+
+            let parent = dir.parent().map(Path::to_path_buf);
+            let mut lines: Vec<String> = parent.iter().map(|_| format!("{}/../", dir.display())).collect();
+            lines.extend(dirs.iter().map(|d| format!("{}/", d.display())));
+            lines.extend(files.iter().map(|f| f.display().to_string()));
+            let Some(i) = self.pick_index(&lines)? else { return Ok(None) };
+            let i = if parent.is_some() { i } else { i + 1 };
+            match i {
+                0 => dir = parent.unwrap_or(dir),
+                i if i <= dirs.len() => dir = dirs[i - 1].clone(),
+                i => return Ok(Some(files[i - 1 - dirs.len()].clone())),
+            }
+        }
+    }
+
+    /// Line-search mode: every line of every entry, with schema docs in the preview window.
+    pub fn pick_line(&self, lines: &[ConfigLine], schema: &Schema, query: Option<&str>) -> Result<Option<LinePick>> {
+        let (tx, rx): (SkimItemSender, SkimItemReceiver) = unbounded();
+        let enable_seg_colors = self.seg_colors && std::env::var("NO_COLOR").is_err();
+        for (i, l) in lines.iter().enumerate() {
+            let (text, display) = build_line_hit(l, enable_seg_colors);
+            let preview = l.option.as_deref().and_then(|o| schema.get(o)).map(|spec| spec.doc());
+            let item = ColoredItem { id_path: i.to_string(), text, display, index: i, preview };
+            let _ = tx.send(Arc::new(item));
+        }
+        drop(tx);
+
+        let mut options = self.options();
+        options.preview = Some(String::new());
+        options.preview_window = "down:30%:wrap".to_string();
+        options.expect = vec!["ctrl-e".to_string(), "ctrl-b".to_string(), "ctrl-t".to_string()];
+        options.query = query.map(str::to_string);
+
+        let out = Skim::run_with(&options, Some(rx));
+        if let Some(out) = out {
+            if out.is_abort { return Ok(None); }
+            if let Some(selected) = out.selected_items.first()
+                && let Ok(i) = selected.output().parse::<usize>()
+                && let Some(line) = lines.get(i)
+            {
+                let action = match out.final_key {
+                    Key::Ctrl('e') => LineAction::Explain,
+                    Key::Ctrl('b') => LineAction::Blame,
+                    Key::Ctrl('t') => LineAction::Bookmark,
+                    _ => LineAction::Open,
+                };
+                return Ok(Some(LinePick { line: line.clone(), action, query: out.query }));
+            }
         }
         Ok(None)
     }
 }
 
-fn build_colored_line(e: &ConfigEntry, seg_colors: bool) -> (String, AnsiString<'static>) {
+fn build_line_hit(l: &ConfigLine, seg_colors: bool) -> (String, AnsiString<'static>) {
+    // Layout: "file:line: text"
+    let loc = format!("{}:{}:", l.path.display(), l.line);
+    let base = format!("{loc} {}", l.text);
+    if !seg_colors {
+        return (base.clone(), base.into());
+    }
+    let loc_attr = Attr { fg: Color::AnsiValue(8), bg: Color::Default, effect: Effect::empty() };
+    let mut fragments = vec![(loc_attr, (0, loc.chars().count() as u32))];
+    if l.option.is_some() {
+        let start = loc.chars().count() + 1;
+        let key_len = l.text.split('=').next().unwrap_or("").trim_end().chars().count();
+        let key_attr = Attr { fg: Color::Rgb(0xDA, 0x68, 0xEC), bg: Color::Default, effect: Effect::BOLD };
+        fragments.push((key_attr, (start as u32, (start + key_len) as u32)));
+    }
+    let ansi = AnsiString::new_string(base.clone(), fragments);
+    (base, ansi)
+}
+
+/// " → target ← source [stow, read-only]" for symlinked or managed files, else empty.
+fn link_note(e: &ConfigEntry) -> String {
+    let Some(link) = &e.link else { return String::new() };
+    let mut out = String::new();
+    if let Some(target) = &link.target {
+        out.push_str(&format!(" → {}", target.display()));
+    }
+    if let Some(source) = &link.source {
+        out.push_str(&format!(" ← {}", source.display()));
+    }
+    let mut flags: Vec<String> = Vec::new();
+    if let Some(m) = link.manager {
+        flags.push(m.to_string());
+    }
+    if link.broken {
+        flags.push("broken".to_string());
+    }
+    if link.read_only {
+        flags.push("read-only".to_string());
+    }
+    if !flags.is_empty() {
+        out.push_str(&format!(" [{}]", flags.join(", ")));
+    }
+    out
+}
+
+/// Header tags, hosts and requirements, e.g. " #input @laptop needs:wl-copy".
+fn header_note(e: &ConfigEntry) -> String {
+    let h = &e.header;
+    let tags = h.tags.iter().map(|t| format!(" #{t}"));
+    let hosts = h.hosts.iter().map(|t| format!(" @{t}"));
+    let requires = h.requires.iter().map(|t| format!(" needs:{t}"));
+    tags.chain(hosts).chain(requires).collect()
+}
+
+fn build_colored_line(live: &LiveEntry, seg_colors: bool) -> (String, AnsiString<'static>) {
+    let e = &live.entry;
     // Build the base (stripped) string and fragment ranges per segment
     let desc = e.description.as_deref().unwrap_or("");
     let sep = if desc.trim().is_empty() { "" } else { " — " };
+    let note = link_note(e);
+    let marker = match live.git {
+        Some(g) => format!("{:<2} ", g.marker()),
+        None => String::new(),
+    };
+    let pin = if live.pinned { "* " } else { "" };
+    let member = match live.in_profile {
+        Some(true) => "● ",
+        Some(false) => "○ ",
+        None => "",
+    };
+    let meta = header_note(e);
     let base = format!(
-        "[{cat}] {alias}{sep}{desc} | {file} ({path})",
+        "{pin}{member}{marker}[{cat}] {alias}{sep}{desc}{meta} | {file} ({path}){note}",
         cat = e.category,
         alias = e.alias,
         sep = sep,
@@ -128,6 +666,36 @@ fn build_colored_line(e: &ConfigEntry, seg_colors: bool) -> (String, AnsiString<
 
     let mut idx: usize = 0; // char index
 
+    // pin marker: magenta
+    if live.pinned {
+        let attr = Attr { fg: Color::AnsiValue(5), bg: Color::Default, effect: Effect::BOLD };
+        fragments.push((attr, (0, 1)));
+        idx = pin.chars().count();
+    }
+
+    // profile membership: green when the active profile sources the entry, grey otherwise
+    if let Some(on) = live.in_profile {
+        let fg = if on { Color::AnsiValue(2) } else { Color::AnsiValue(8) };
+        let attr = Attr { fg, bg: Color::Default, effect: Effect::empty() };
+        fragments.push((attr, (idx as u32, idx as u32 + 1)));
+        idx += member.chars().count();
+    }
+
+    // git status marker: green staged, red modified, grey untracked/ignored
+    if let Some(g) = live.git {
+        for c in g.marker().chars() {
+            let fg = match c {
+                'S' => Color::AnsiValue(2),
+                'M' => Color::AnsiValue(1),
+                _ => Color::AnsiValue(8),
+            };
+            let attr = Attr { fg, bg: Color::Default, effect: Effect::BOLD };
+            fragments.push((attr, (idx as u32, idx as u32 + 1)));
+            idx += 1;
+        }
+        idx = pin.chars().count() + member.chars().count() + marker.chars().count();
+    }
+
     // "["
     idx += "[".chars().count();
     // category text start
@@ -154,6 +722,14 @@ fn build_colored_line(e: &ConfigEntry, seg_colors: bool) -> (String, AnsiString<
         idx += desc_len;
     }
 
+    // header tags: cyan
+    if !meta.is_empty() {
+        let meta_len = meta.chars().count();
+        let meta_attr = Attr { fg: Color::AnsiValue(6), bg: Color::Default, effect: Effect::empty() };
+        fragments.push((meta_attr, (idx as u32, (idx + meta_len) as u32)));
+        idx += meta_len;
+    }
+
     // space before pipe
     idx += " ".chars().count();
     // trailing file+path start at current idx including "| " and rest
@@ -161,6 +737,51 @@ fn build_colored_line(e: &ConfigEntry, seg_colors: bool) -> (String, AnsiString<
     let file_len = file_trail.chars().count();
     let file_attr = Attr { fg: Color::AnsiValue(15), bg: Color::Default, effect: Effect::empty() };
     fragments.push((file_attr, (idx as u32, (idx + file_len) as u32)));
+    idx += file_len;
+
+    // symlink target and flags; red when broken
+    if !note.is_empty() {
+        let broken = e.link.as_ref().is_some_and(|l| l.broken);
+        let fg = if broken { Color::AnsiValue(1) } else { Color::AnsiValue(8) };
+        let note_attr = Attr { fg, bg: Color::Default, effect: Effect::empty() };
+        fragments.push((note_attr, (idx as u32, (idx + note.chars().count()) as u32)));
+    }
+
+    let ansi = AnsiString::new_string(base.clone(), fragments);
+    (base, ansi)
+}
+
+fn build_swatch_line(c: &PaletteEntry) -> (String, AnsiString<'static>) {
+    // Layout: "<swatch> name  value | file:line"; the swatch is blank cells with an Rgb background
+    const SWATCH: usize = 8;
+    let base = format!(
+        "{swatch} {name}  {value} | {file}:{line}",
+        swatch = " ".repeat(SWATCH),
+        name = c.name,
+        value = c.raw,
+        file = c.path.display(),
+        line = c.line,
+    );
+
+    let mut fragments: Vec<(Attr, (u32, u32))> = Vec::new();
+    // Split the swatch evenly across gradient stops; the last stop takes the remainder
+    let per = (SWATCH / c.value.stops.len()).max(1);
+    for (i, stop) in c.value.stops.iter().enumerate().take(SWATCH) {
+        let start = i * per;
+        let end = if i + 1 == c.value.stops.len() { SWATCH } else { start + per };
+        let attr = Attr { fg: Color::Default, bg: Color::Rgb(stop.r, stop.g, stop.b), effect: Effect::empty() };
+        fragments.push((attr, (start as u32, end as u32)));
+    }
+
+    let mut idx = SWATCH + 1;
+    let name_len = c.name.chars().count();
+    let name_attr = Attr { fg: Color::Rgb(0xDA, 0x68, 0xEC), bg: Color::Default, effect: Effect::BOLD };
+    fragments.push((name_attr, (idx as u32, (idx + name_len) as u32)));
+    idx += name_len + 2 + c.raw.chars().count() + 1;
+
+    let file_trail = format!("| {}:{}", c.path.display(), c.line);
+    let file_attr = Attr { fg: Color::AnsiValue(15), bg: Color::Default, effect: Effect::empty() };
+    fragments.push((file_attr, (idx as u32, (idx + file_trail.chars().count()) as u32)));
 
     let ansi = AnsiString::new_string(base.clone(), fragments);
     (base, ansi)
@@ -0,0 +1,356 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use hyprconf::xdg;
+
+use crate::{
+    cli::{WallpaperArgs, WallpaperCommand},
+    ipc,
+    lint::{Diagnostic, Severity},
+    model::ConfigEntry,
+    parse::{self, LineKind},
+    ui::Picker,
+};
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp", "jxl", "bmp"];
+
+#[derive(Debug, Clone)]
+pub struct Preload {
+    pub line: usize,
+    pub raw: String,
+    pub path: PathBuf,
+}
+
+/// `wallpaper = monitor,[fit:]path`; an empty monitor applies to every monitor.
+#[derive(Debug, Clone)]
+pub struct Wallpaper {
+    pub line: usize,
+    pub monitor: String,
+    /// Fit mode prefix such as `contain:` or `tile:`
+    pub mode: Option<String>,
+    pub raw: String,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone)]
+pub struct PaperConfig {
+    pub path: PathBuf,
+    pub preloads: Vec<Preload>,
+    pub wallpapers: Vec<Wallpaper>,
+    /// Other keys (splash, ipc, ...) with their values
+    pub other: Vec<(String, String)>,
+}
+
+fn expand(raw: &str) -> PathBuf {
+    xdg::expand_tilde(raw.trim()).unwrap_or_else(|_| PathBuf::from(raw.trim()))
+}
+
+fn split_wallpaper(value: &str) -> (String, Option<String>, String) {
+    let (monitor, rest) = value.split_once(',').unwrap_or(("", value));
+    let rest = rest.trim();
+    match rest.split_once(':') {
+        Some((mode, path)) if !mode.contains('/') && !mode.starts_with('~') => {
+            (monitor.trim().to_string(), Some(mode.to_string()), path.trim().to_string())
+        }
+        _ => (monitor.trim().to_string(), None, rest.to_string()),
+    }
+}
+
+pub fn parse_paper(path: &Path) -> Result<PaperConfig> {
+    let mut cfg = PaperConfig { path: path.to_path_buf(), preloads: Vec::new(), wallpapers: Vec::new(), other: Vec::new() };
+    for line in parse::parse_file(path)? {
+        let LineKind::Assign { key, value, .. } = &line.kind else { continue };
+        match key.as_str() {
+            "preload" => cfg.preloads.push(Preload { line: line.number, raw: value.clone(), path: expand(value) }),
+            "wallpaper" => {
+                let (monitor, mode, raw) = split_wallpaper(value);
+                cfg.wallpapers.push(Wallpaper { line: line.number, monitor, mode, path: expand(&raw), raw });
+            }
+            _ => cfg.other.push((key.clone(), value.clone())),
+        }
+    }
+    Ok(cfg)
+}
+
+pub fn check(cfg: &PaperConfig) -> Vec<Diagnostic> {
+    let mut out = Vec::new();
+    let mut diag = |line, severity, message| out.push(Diagnostic { path: cfg.path.clone(), line, severity, message });
+    for p in &cfg.preloads {
+        if !p.path.is_file() {
+            diag(p.line, Severity::Error, format!("preloaded image does not exist: {}", p.raw));
+        }
+        if !cfg.wallpapers.iter().any(|w| w.path == p.path) {
+            diag(p.line, Severity::Warning, format!("preload is never used by a wallpaper: {}", p.raw));
+        }
+    }
+    for w in &cfg.wallpapers {
+        if !w.path.is_file() {
+            diag(w.line, Severity::Error, format!("wallpaper image does not exist: {}", w.raw));
+        }
+        if !cfg.preloads.iter().any(|p| p.path == w.path) {
+            diag(w.line, Severity::Error, format!("wallpaper is not preloaded: {}", w.raw));
+        }
+    }
+    out
+}
+
+/// hyprpaper's own IPC socket for the running Hyprland instance.
+pub fn hyprpaper_socket() -> Option<PathBuf> {
+    ipc::instance_dir().map(|d| d.join(".hyprpaper.sock")).filter(|p| p.exists())
+}
+
+/// Preload and show `image` on `monitor` through hyprpaper's socket.
+pub fn apply_live(socket: &Path, monitor: &str, image: &Path) -> Result<()> {
+    let image = image.display();
+    for cmd in [format!("preload {image}"), format!("wallpaper {monitor},{image}")] {
+        let reply = ipc::request(socket, &cmd)?;
+        if !reply.trim().is_empty() && reply.trim() != "ok" {
+            bail!("hyprpaper rejected {cmd:?}: {}", reply.trim());
+        }
+    }
+    Ok(())
+}
+
+/// Point `monitor` at `image` in the file contents, replacing its `wallpaper =` line (or
+/// appending one) and adding a `preload =` when missing. Fit-mode prefixes are kept. The
+/// replaced image's `preload =` is rewritten for the new image, or dropped, once nothing else
+/// shows it.
+pub fn set_wallpaper_str(src: &str, cfg: &PaperConfig, monitor: &str, image: &Path) -> String {
+    let image_str = image.display().to_string();
+    let indent_of = |raw: &str| raw[..raw.len() - raw.trim_start().len()].to_string();
+    // None marks a dropped line; indices stay valid until the end
+    let mut lines: Vec<Option<String>> = src.lines().map(|l| Some(l.to_string())).collect();
+    let mut preloaded = cfg.preloads.iter().any(|p| p.path == image);
+
+    let old = cfg.wallpapers.iter().find(|w| w.monitor == monitor);
+    match old {
+        Some(w) => {
+            let indent = indent_of(lines[w.line - 1].as_deref().unwrap_or_default());
+            let mode = w.mode.as_deref().map(|m| format!("{m}:")).unwrap_or_default();
+            lines[w.line - 1] = Some(format!("{indent}wallpaper = {monitor},{mode}{image_str}"));
+        }
+        None => lines.push(Some(format!("wallpaper = {monitor},{image_str}"))),
+    }
+
+    if let Some(w) = old
+        && w.path != image
+        && !cfg.wallpapers.iter().any(|o| o.line != w.line && o.path == w.path)
+    {
+        for p in cfg.preloads.iter().filter(|p| p.path == w.path) {
+            lines[p.line - 1] = if preloaded {
+                None
+            } else {
+                preloaded = true;
+                let indent = indent_of(lines[p.line - 1].as_deref().unwrap_or_default());
+                Some(format!("{indent}preload = {image_str}"))
+            };
+        }
+    }
+
+    if !preloaded {
+        // Keep preloads together: insert after the last one, else at the top
+        let at = cfg.preloads.iter().map(|p| p.line).max().unwrap_or(0);
+        lines.insert(at, Some(format!("preload = {image_str}")));
+    }
+
+    let mut out = lines.into_iter().flatten().collect::<Vec<_>>().join("\n");
+    out.push('\n');
+    out
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension().is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_string_lossy().to_lowercase().as_str()))
+}
+
+fn confirm(prompt: &str) -> Result<bool> {
+    print!("{prompt} [y/N] ");
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+pub fn run(args: &WallpaperArgs, root: &Path, entries: &[ConfigEntry], picker: &Picker) -> Result<()> {
+    let path = entries
+        .iter()
+        .find(|e| e.file_name == "hyprpaper.conf")
+        .map(|e| e.path.clone())
+        .unwrap_or_else(|| root.join("hyprpaper.conf"));
+    if !path.exists() {
+        bail!("no hyprpaper.conf in {}", root.display());
+    }
+    let cfg = parse_paper(&path)?;
+
+    match &args.command {
+        None => {
+            println!("hyprpaper ({})\n", path.display());
+            for w in &cfg.wallpapers {
+                let monitor = if w.monitor.is_empty() { "(all)" } else { &w.monitor };
+                let mode = w.mode.as_deref().map(|m| format!(" [{m}]")).unwrap_or_default();
+                println!("  {monitor:<12} {}{mode}", w.raw);
+            }
+            for (key, value) in &cfg.other {
+                println!("  {key} = {value}");
+            }
+            let diags = check(&cfg);
+            if !diags.is_empty() {
+                println!();
+                for d in &diags {
+                    println!("{d}");
+                }
+            }
+            Ok(())
+        }
+        Some(WallpaperCommand::Set { monitor, dir, apply }) => {
+            let dir = match dir {
+                Some(d) => d.clone(),
+                // Default to the folder the monitor's current wallpaper lives in
+                None => cfg
+                    .wallpapers
+                    .iter()
+                    .find(|w| w.monitor == *monitor)
+                    .or(cfg.wallpapers.first())
+                    .and_then(|w| w.path.parent().map(Path::to_path_buf))
+                    .context("no wallpaper directory known; pass --dir")?,
+            };
+            let Some(image) = picker.pick_path(&dir, is_image)? else { return Ok(()) };
+
+            let src = fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+            fs::write(&path, set_wallpaper_str(&src, &cfg, monitor, &image))
+                .with_context(|| format!("writing {}", path.display()))?;
+            let shown = if monitor.is_empty() { "all monitors" } else { monitor.as_str() };
+            println!("{shown}: {}", image.display());
+
+            if let Some(socket) = hyprpaper_socket()
+                && (*apply || confirm("hyprpaper is running; apply now?")?)
+            {
+                apply_live(&socket, monitor, &image)?;
+                println!("applied");
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Read, os::unix::net::UnixListener, thread};
+
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("hyprconf-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn paper(dir: &Path, src: &str) -> PaperConfig {
+        let path = dir.join("hyprpaper.conf");
+        fs::write(&path, src).unwrap();
+        parse_paper(&path).unwrap()
+    }
+
+    #[test]
+    fn check_flags_missing_unpreloaded_and_unused_images() {
+        let dir = temp_dir("paper-check");
+        for image in ["shown.png", "spare.png", "plain.png"] {
+            fs::write(dir.join(image), b"").unwrap();
+        }
+        let d = dir.display();
+        let cfg = paper(
+            &dir,
+            &format!(
+                "preload = {d}/shown.png\npreload = {d}/spare.png\npreload = {d}/gone.png\n\
+                 wallpaper = DP-1,{d}/shown.png\nwallpaper = DP-2,contain:{d}/plain.png\n\
+                 wallpaper = ,{d}/gone.png\nsplash = false\n"
+            ),
+        );
+
+        assert_eq!(cfg.preloads.len(), 3);
+        let monitors: Vec<_> = cfg.wallpapers.iter().map(|w| (w.monitor.as_str(), w.mode.as_deref())).collect();
+        assert_eq!(monitors, [("DP-1", None), ("DP-2", Some("contain")), ("", None)]);
+        assert_eq!(cfg.wallpapers[1].path, dir.join("plain.png"));
+        assert_eq!(cfg.other, [("splash".to_string(), "false".to_string())]);
+
+        let diags: Vec<_> = check(&cfg).into_iter().map(|d| (d.line, d.severity, d.message)).collect();
+        assert_eq!(
+            diags,
+            [
+                (2, Severity::Warning, format!("preload is never used by a wallpaper: {d}/spare.png")),
+                (3, Severity::Error, format!("preloaded image does not exist: {d}/gone.png")),
+                (5, Severity::Error, format!("wallpaper is not preloaded: {d}/plain.png")),
+                (6, Severity::Error, format!("wallpaper image does not exist: {d}/gone.png")),
+            ]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn set_wallpaper_replaces_the_old_preload_once_unused() {
+        let dir = temp_dir("paper-set");
+        let src = "preload = /w/old.png\npreload = /w/shared.png\n\
+                   wallpaper = DP-1,/w/old.png\nwallpaper = DP-2,/w/shared.png\nwallpaper = DP-3,/w/shared.png\n";
+
+        // old.png is no longer shown anywhere: its preload becomes the new image's
+        let out = set_wallpaper_str(src, &paper(&dir, src), "DP-1", Path::new("/w/new.png"));
+        assert_eq!(
+            out,
+            "preload = /w/new.png\npreload = /w/shared.png\n\
+             wallpaper = DP-1,/w/new.png\nwallpaper = DP-2,/w/shared.png\nwallpaper = DP-3,/w/shared.png\n"
+        );
+        assert!(check(&paper(&dir, &out)).iter().all(|d| !d.message.starts_with("preload is never used")));
+
+        // DP-3 still shows shared.png, so its preload stays; new.png is already preloaded
+        let out = set_wallpaper_str(&out, &paper(&dir, &out), "DP-2", Path::new("/w/new.png"));
+        assert_eq!(
+            out,
+            "preload = /w/new.png\npreload = /w/shared.png\n\
+             wallpaper = DP-1,/w/new.png\nwallpaper = DP-2,/w/new.png\nwallpaper = DP-3,/w/shared.png\n"
+        );
+
+        // Now shared.png is unused and new.png is already preloaded: the line goes
+        let out = set_wallpaper_str(&out, &paper(&dir, &out), "DP-3", Path::new("/w/new.png"));
+        assert_eq!(
+            out,
+            "preload = /w/new.png\n\
+             wallpaper = DP-1,/w/new.png\nwallpaper = DP-2,/w/new.png\nwallpaper = DP-3,/w/new.png\n"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn apply_live_sends_preload_then_wallpaper() {
+        let runtime = temp_dir("paper-ipc");
+        let instance = runtime.join("hypr").join("test-signature");
+        fs::create_dir_all(&instance).unwrap();
+        let listener = UnixListener::bind(instance.join(".hyprpaper.sock")).unwrap();
+        // SAFETY: no other test reads or writes the environment
+        unsafe {
+            std::env::set_var("XDG_RUNTIME_DIR", &runtime);
+            std::env::set_var("HYPRLAND_INSTANCE_SIGNATURE", "test-signature");
+        }
+
+        // hyprpaper answers each request on its own connection and closes it
+        let server = thread::spawn(move || {
+            let mut received = Vec::new();
+            for stream in listener.incoming().take(2) {
+                let mut stream = stream.unwrap();
+                let mut buf = [0; 512];
+                let n = stream.read(&mut buf).unwrap();
+                received.push(String::from_utf8_lossy(&buf[..n]).into_owned());
+                stream.write_all(b"ok").unwrap();
+            }
+            received
+        });
+
+        let socket = hyprpaper_socket().expect("socket under $XDG_RUNTIME_DIR/hypr/<signature>");
+        apply_live(&socket, "DP-1", Path::new("/w/new.png")).unwrap();
+        assert_eq!(server.join().unwrap(), ["preload /w/new.png", "wallpaper DP-1,/w/new.png"]);
+        fs::remove_dir_all(&runtime).unwrap();
+    }
+}
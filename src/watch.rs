@@ -0,0 +1,128 @@
+use std::{
+    sync::{Arc, Mutex, RwLock, mpsc},
+    thread,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use notify::{
+    Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher,
+    event::{AccessKind, AccessMode},
+};
+
+use crate::{
+    git::{self, GitStatus},
+    model::ConfigEntry,
+    query::Filter,
+    scan::Scanner,
+};
+
+/// Editors write files in several steps; wait this long for the burst to settle.
+const DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Latest state of one picker entry, and what the picker shows beside the scanned entry; the
+/// picker reads it on every redraw.
+#[derive(Debug, Clone)]
+pub struct LiveEntry {
+    pub entry: ConfigEntry,
+    /// Set when the file (or its symlink target) is inside a git work tree
+    pub git: Option<GitStatus>,
+    /// Pinned to the top of the picker
+    pub pinned: bool,
+    /// Whether the active profile sources the entry; None without a profile, or for
+    /// categories profiles don't manage
+    pub in_profile: Option<bool>,
+    /// The file disappeared since the picker started
+    pub removed: bool,
+}
+
+impl LiveEntry {
+    pub fn new(entry: ConfigEntry) -> Self {
+        LiveEntry { entry, git: None, pinned: false, in_profile: None, removed: false }
+    }
+}
+
+pub type Slot = Arc<RwLock<LiveEntry>>;
+
+/// Every entry found so far, in the order found (the picker's item ids).
+pub type Slots = Arc<Mutex<Vec<Slot>>>;
+
+pub fn slot(live: LiveEntry) -> Slot {
+    Arc::new(RwLock::new(live))
+}
+
+/// Changes that can affect entries. Our own rescans open files and `git status` touches
+/// `.git/`; reacting to those would loop forever.
+fn relevant(event: &notify::Result<Event>) -> bool {
+    let Ok(event) = event else { return false };
+    let kind = matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) | EventKind::Access(AccessKind::Close(AccessMode::Write))
+    );
+    kind && event.paths.iter().any(|p| !p.components().any(|c| c.as_os_str() == ".git"))
+}
+
+/// Rescan `root` and bring the slots up to date, keeping pins and profile marks; new entries
+/// get new slots at the end.
+fn refresh(scanner: &Scanner, filter: &Filter, slots: &Slots) {
+    let Ok(mut entries) = scanner.scan() else { return };
+    entries.retain(|e| filter.matches(e));
+    let mut entries: Vec<LiveEntry> = entries.into_iter().map(LiveEntry::new).collect();
+    git::annotate(&mut entries);
+
+    let mut slots = slots.lock().unwrap();
+    for s in slots.iter() {
+        let mut live = s.write().unwrap();
+        match entries.iter().position(|e| e.entry.path == live.entry.path) {
+            Some(i) => {
+                let new = entries.swap_remove(i);
+                (live.entry, live.git, live.removed) = (new.entry, new.git, false);
+            }
+            None => live.removed = true,
+        }
+    }
+    slots.extend(entries.into_iter().map(slot));
+}
+
+/// Initial scan: each entry gets a slot as soon as it is read. Once the scan completes the
+/// slots are rewritten with what `order` makes of the entries (they come to it in `sort_key`
+/// order): sorted, with git status, pins and profile marks filled in.
+pub fn stream(
+    scanner: &Scanner,
+    filter: &Filter,
+    slots: &Slots,
+    order: impl FnOnce(Vec<ConfigEntry>) -> Vec<LiveEntry>,
+) -> Result<()> {
+    let wanted = |e: &ConfigEntry| filter.matches(e);
+    let mut sorted = scanner.scan_with(|e| {
+        if wanted(e) {
+            slots.lock().unwrap().push(slot(LiveEntry::new(e.clone())));
+        }
+    })?;
+    sorted.retain(wanted);
+    for (s, live) in slots.lock().unwrap().iter().zip(order(sorted)) {
+        *s.write().unwrap() = live;
+    }
+    Ok(())
+}
+
+/// Watch the scanner's root recursively and rescan into `slots` on changes. Watching stops
+/// when the returned watcher is dropped.
+pub fn spawn(scanner: Scanner, filter: Filter, slots: Slots) -> Result<RecommendedWatcher> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).context("starting file watcher")?;
+    watcher
+        .watch(scanner.root(), RecursiveMode::Recursive)
+        .with_context(|| format!("watching {}", scanner.root().display()))?;
+
+    thread::spawn(move || {
+        while let Ok(event) = rx.recv() {
+            if !relevant(&event) {
+                continue;
+            }
+            while rx.recv_timeout(DEBOUNCE).is_ok() {}
+            refresh(&scanner, &filter, &slots);
+        }
+    });
+    Ok(watcher)
+}
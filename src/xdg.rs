@@ -0,0 +1,23 @@
+use std::{env, path::PathBuf};
+
+use crate::{Error, Result};
+
+/// Resolve an XDG base directory, falling back to `$HOME/<fallback>`.
+pub fn dir(var: &str, fallback: &str) -> Result<PathBuf> {
+    if let Ok(dir) = env::var(var)
+        && !dir.trim().is_empty()
+    {
+        return Ok(PathBuf::from(dir));
+    }
+    let home = env::var("HOME").map_err(|_| Error::NoHome)?;
+    Ok(PathBuf::from(home).join(fallback))
+}
+
+/// Expand a leading `~/` (or `$HOME/`, as in shell commands) so paths can be written like in
+/// Hyprland's configs.
+pub fn expand_tilde(value: &str) -> Result<PathBuf> {
+    match ["~/", "$HOME/", "${HOME}/"].iter().find_map(|p| value.strip_prefix(p)) {
+        Some(rest) => Ok(PathBuf::from(env::var("HOME").map_err(|_| Error::NoHome)?).join(rest)),
+        None => Ok(PathBuf::from(value)),
+    }
+}